@@ -37,6 +37,17 @@ where
     <P as Decodable>::Decoder: rodio::Source + Send + Sync,
     <<P as Decodable>::Decoder as Iterator>::Item: rodio::Sample + Send + Sync,
 {
+    /// Queues an audio source to be played once. The sound needs no further handling: it starts
+    /// as soon as the source is loaded and is cleaned up when it finishes.
+    ///
+    /// ```
+    /// # use bevy_asset::AssetServer;
+    /// # use bevy_audio::Audio;
+    /// # use bevy_ecs::system::Res;
+    /// fn play_pickup_sound(asset_server: Res<AssetServer>, audio: Res<Audio>) {
+    ///     audio.play(asset_server.load("sounds/pickup.mp3"));
+    /// }
+    /// ```
     pub fn play(&self, audio_source: Handle<P>) {
         self.queue.write().push_front(audio_source);
     }
@@ -1,16 +1,41 @@
 use crate::{Audio, AudioSource, Decodable};
-use bevy_asset::{Asset, Assets};
+use bevy_asset::{Asset, AssetServer, Assets, LoadState};
 use bevy_ecs::world::World;
-use rodio::{OutputStream, OutputStreamHandle, Sink};
-use std::marker::PhantomData;
+use bevy_utils::{
+    tracing::{error, info, warn},
+    Duration, Instant,
+};
+use rodio::{
+    cpal::{
+        self,
+        traits::{DeviceTrait, HostTrait},
+    },
+    Device, OutputStream, OutputStreamHandle, Sink, StreamError,
+};
+use std::{
+    marker::PhantomData,
+    sync::mpsc::{self, Receiver, TryRecvError},
+    thread,
+};
+
+/// How often the output checks whether its device is still connected while sounds are played
+const DEVICE_CHECK_INTERVAL: Duration = Duration::from_secs(1);
 
 /// Used internally to play audio on the current "audio device"
+///
+/// Falls back to the default device when its device is disconnected, unless one was chosen with
+/// [AudioOutput::reinitialize]. Without any device, queued audio is discarded.
 pub struct AudioOutput<P = AudioSource>
 where
     P: Decodable,
 {
-    _stream: OutputStream,
-    stream_handle: OutputStreamHandle,
+    _stream: Option<OutputStream>,
+    stream_handle: Option<OutputStreamHandle>,
+    follows_default_device: bool,
+    device_name: Option<String>,
+    /// Receives whether the device was lost from the check running on a background thread
+    device_check: Option<Receiver<bool>>,
+    last_device_check: Option<Instant>,
     phantom: PhantomData<P>,
 }
 
@@ -19,12 +44,183 @@ where
     P: Decodable,
 {
     fn default() -> Self {
-        let (stream, stream_handle) = OutputStream::try_default().unwrap();
-
-        Self {
-            _stream: stream,
-            stream_handle,
+        let mut audio_output = Self {
+            _stream: None,
+            stream_handle: None,
+            follows_default_device: true,
+            device_name: None,
+            device_check: None,
+            last_device_check: None,
             phantom: PhantomData,
+        };
+        audio_output.reinitialize_default();
+        audio_output
+    }
+}
+
+/// Returns the names of the output devices of the default audio host, e.g. to list them in an
+/// audio settings menu. Returns an empty list if the devices can't be enumerated.
+///
+/// Use [`cpal::default_host().output_devices()`](cpal::traits::HostTrait::output_devices) to get
+/// the [Device]s themselves for [AudioOutput::reinitialize].
+pub fn available_output_devices() -> Vec<String> {
+    match cpal::default_host().output_devices() {
+        Ok(devices) => devices.filter_map(|device| device.name().ok()).collect(),
+        Err(err) => {
+            warn!("Failed to enumerate audio output devices: {}", err);
+            Vec::new()
+        }
+    }
+}
+
+/// Returns `true` if the output devices could be listed and none of them is named `device_name`.
+/// A device whose name can't be read might be the one in use, so it counts as still connected.
+fn is_device_lost(device_name: &str) -> bool {
+    let devices = match cpal::default_host().output_devices() {
+        Ok(devices) => devices,
+        Err(_) => return false,
+    };
+    let mut lost = true;
+    for device in devices {
+        match device.name() {
+            Ok(name) if name == device_name => return false,
+            Ok(_) => {}
+            Err(_) => lost = false,
+        }
+    }
+    lost
+}
+
+/// Logs the default output config of `device`, which rodio opens its streams with. rodio doesn't
+/// expose the config of an opened stream, so this is what the stream uses unless rodio had to fall
+/// back to another supported config.
+fn log_default_output_config(device: &Device) {
+    let name = device.name().unwrap_or_else(|_| "<unknown>".to_string());
+    match device.default_output_config() {
+        Ok(config) => info!(
+            "Audio output device: {} (default config: {} channels, {} Hz, {:?}, buffer size: {:?})",
+            name,
+            config.channels(),
+            config.sample_rate().0,
+            config.sample_format(),
+            config.buffer_size(),
+        ),
+        Err(err) => warn!(
+            "Failed to query the default output config of audio device {}: {}",
+            name, err
+        ),
+    }
+}
+
+impl<P> AudioOutput<P>
+where
+    P: Decodable,
+{
+    /// Returns `true` if an audio output device was opened and queued audio will be played.
+    /// Systems can read this through `NonSend<AudioOutput>` to tell the user that audio is
+    /// disabled.
+    pub fn is_available(&self) -> bool {
+        self.stream_handle.is_some()
+    }
+
+    /// Closes the current output stream and opens a new one on `device`, or on the system's
+    /// default output device if `device` is `None`. Use this to switch devices, or to recover
+    /// after the device was unplugged. Sounds that were playing on the old stream stop. With
+    /// `None`, the output keeps following the default device when its device is disconnected.
+    ///
+    /// The old stream is dropped before the new one is opened, so the device is released even if
+    /// opening fails, which leaves the output unavailable. `AudioOutput` is a non-send resource
+    /// because rodio's stream must stay on the thread that created it, so call this from an
+    /// exclusive system or through `NonSendMut<AudioOutput>`.
+    pub fn reinitialize(&mut self, device: Option<&Device>) -> Result<(), StreamError> {
+        self.stream_handle = None;
+        self._stream = None;
+        self.follows_default_device = device.is_none();
+        self.device_name = None;
+        self.device_check = None;
+
+        let (stream, stream_handle) = match device {
+            Some(device) => {
+                let stream = OutputStream::try_from_device(device)?;
+                log_default_output_config(device);
+                self.device_name = device.name().ok();
+                stream
+            }
+            None => {
+                let stream = OutputStream::try_default()?;
+                if let Some(device) = cpal::default_host().default_output_device() {
+                    log_default_output_config(&device);
+                    self.device_name = device.name().ok();
+                }
+                stream
+            }
+        };
+        self._stream = Some(stream);
+        self.stream_handle = Some(stream_handle);
+        Ok(())
+    }
+
+    /// Opens the default device, leaving the output unavailable with a warning if that fails
+    fn reinitialize_default(&mut self) {
+        if let Err(err) = self.reinitialize(None) {
+            warn!(
+                "No audio output device available, audio is disabled: {}",
+                err
+            );
+        }
+    }
+
+    /// Reopens the stream on the default device if the output follows the default device and
+    /// either a background check found that the device it plays on was disconnected, or no device
+    /// could be opened before. Starts a new background check if the last one is old enough.
+    fn follow_default_device(&mut self) {
+        if !self.follows_default_device {
+            return;
+        }
+        if let Some(device_check) = &self.device_check {
+            match device_check.try_recv() {
+                Ok(true) => {
+                    info!(
+                        "Audio output device {} was disconnected, switching to the default device",
+                        self.device_name.as_deref().unwrap_or("<unknown>")
+                    );
+                    self.reinitialize_default();
+                    return;
+                }
+                Ok(false) | Err(TryRecvError::Disconnected) => self.device_check = None,
+                Err(TryRecvError::Empty) => return,
+            }
+        }
+
+        if self.last_device_check.map_or(false, |last_check| {
+            last_check.elapsed() < DEVICE_CHECK_INTERVAL
+        }) {
+            return;
+        }
+        self.last_device_check = Some(Instant::now());
+
+        if self.stream_handle.is_none() {
+            if cpal::default_host().default_output_device().is_none() {
+                return;
+            }
+            self.reinitialize_default();
+            return;
+        }
+
+        // without a name there is no telling whether the device is still connected
+        let device_name = match &self.device_name {
+            Some(device_name) => device_name.clone(),
+            None => return,
+        };
+        let (sender, receiver) = mpsc::channel();
+        let spawned = thread::Builder::new()
+            .name("audio device check".to_string())
+            .spawn(move || {
+                // the output may have been dropped or reopened in the meantime
+                let _ = sender.send(is_device_lost(&device_name));
+            });
+        if spawned.is_ok() {
+            self.device_check = Some(receiver);
         }
     }
 }
@@ -35,20 +231,47 @@ where
     <P as Decodable>::Decoder: rodio::Source + Send + Sync,
     <<P as Decodable>::Decoder as Iterator>::Item: rodio::Sample + Send + Sync,
 {
-    fn play_source(&self, audio_source: &P) {
-        let sink = Sink::try_new(&self.stream_handle).unwrap();
+    fn play_source(stream_handle: &OutputStreamHandle, audio_source: &P) {
+        let sink = Sink::try_new(stream_handle).unwrap();
         sink.append(audio_source.decoder());
         sink.detach();
     }
 
-    fn try_play_queued(&self, audio_sources: &Assets<P>, audio: &mut Audio<P>) {
+    fn try_play_queued(
+        &self,
+        audio_sources: &Assets<P>,
+        asset_server: Option<&AssetServer>,
+        audio: &mut Audio<P>,
+    ) {
         let mut queue = audio.queue.write();
+        let stream_handle = match &self.stream_handle {
+            Some(stream_handle) => stream_handle,
+            None => {
+                // there is nothing to play on, so don't let the queue grow forever
+                queue.clear();
+                return;
+            }
+        };
         let len = queue.len();
         let mut i = 0;
         while i < len {
             let audio_source_handle = queue.pop_back().unwrap();
             if let Some(audio_source) = audio_sources.get(&audio_source_handle) {
-                self.play_source(audio_source);
+                Self::play_source(stream_handle, audio_source);
+            } else if let Some(asset_server) = asset_server.filter(|asset_server| {
+                asset_server.get_load_state(&audio_source_handle) == LoadState::Failed
+            }) {
+                // the audio source will never load, so drop it instead of waiting forever
+                match asset_server.get_handle_path(&audio_source_handle) {
+                    Some(path) => error!(
+                        "Failed to load audio source {}, it will not be played",
+                        path.path().display()
+                    ),
+                    None => error!(
+                        "Failed to load audio source {:?}, it will not be played",
+                        audio_source_handle.id
+                    ),
+                }
             } else {
                 // audio source hasn't loaded yet. add it back to the queue
                 queue.push_front(audio_source_handle);
@@ -66,10 +289,21 @@ where
     <<P as Decodable>::Decoder as Iterator>::Item: rodio::Sample + Send + Sync,
 {
     let world = world.cell();
-    let audio_output = world.get_non_send::<AudioOutput<P>>().unwrap();
+    let mut audio_output = world.get_non_send_mut::<AudioOutput<P>>().unwrap();
     let mut audio = world.get_resource_mut::<Audio<P>>().unwrap();
 
+    let asset_server = world.get_resource::<AssetServer>();
+
     if let Some(audio_sources) = world.get_resource::<Assets<P>>() {
-        audio_output.try_play_queued(&*audio_sources, &mut *audio);
+        // only watch the device while sounds are being played on it
+        let starts_playing = audio
+            .queue
+            .read()
+            .iter()
+            .any(|audio_source_handle| audio_sources.contains(audio_source_handle));
+        if starts_playing {
+            audio_output.follow_default_device();
+        }
+        audio_output.try_play_queued(&*audio_sources, asset_server.as_deref(), &mut *audio);
     };
 }
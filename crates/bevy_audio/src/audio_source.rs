@@ -2,6 +2,7 @@ use anyhow::Result;
 use bevy_asset::{AssetLoader, LoadContext, LoadedAsset};
 use bevy_reflect::TypeUuid;
 use bevy_utils::BoxedFuture;
+use rodio::{buffer::SamplesBuffer, decoder::DecoderError};
 use std::{io::Cursor, sync::Arc};
 
 /// A source of audio data
@@ -11,6 +12,33 @@ pub struct AudioSource {
     pub bytes: Arc<[u8]>,
 }
 
+impl AudioSource {
+    /// Creates an [`AudioSource`] from the bytes of an encoded audio file that is already in
+    /// memory, such as one generated at runtime or received over the network.
+    ///
+    /// The format is detected from the bytes when the sound is played, just like for loaded
+    /// files. Add the source to [`Assets<AudioSource>`](bevy_asset::Assets) to get a handle that
+    /// can be passed to [`Audio::play`](crate::Audio::play).
+    ///
+    /// # Panics
+    ///
+    /// Playing the source panics if its bytes aren't in a supported format. Use
+    /// [`AudioSource::try_from_bytes`] for bytes that may be malformed.
+    pub fn from_bytes(bytes: impl Into<Arc<[u8]>>) -> Self {
+        Self {
+            bytes: bytes.into(),
+        }
+    }
+
+    /// Like [`AudioSource::from_bytes`], but returns an error instead if the format of the bytes
+    /// can't be detected or isn't one of the enabled formats.
+    pub fn try_from_bytes(bytes: impl Into<Arc<[u8]>>) -> Result<Self, DecoderError> {
+        let audio_source = Self::from_bytes(bytes);
+        rodio::Decoder::new(Cursor::new(audio_source.clone()))?;
+        Ok(audio_source)
+    }
+}
+
 impl AsRef<[u8]> for AudioSource {
     fn as_ref(&self) -> &[u8] {
         &self.bytes
@@ -23,9 +51,7 @@ pub struct Mp3Loader;
 
 impl AssetLoader for Mp3Loader {
     fn load(&self, bytes: &[u8], load_context: &mut LoadContext) -> BoxedFuture<Result<()>> {
-        load_context.set_default_asset(LoadedAsset::new(AudioSource {
-            bytes: bytes.into(),
-        }));
+        load_context.set_default_asset(LoadedAsset::new(AudioSource::from_bytes(bytes)));
         Box::pin(async move { Ok(()) })
     }
 
@@ -34,6 +60,40 @@ impl AssetLoader for Mp3Loader {
     }
 }
 
+/// A source of raw, interleaved PCM samples that are already in memory
+///
+/// Useful for audio that is generated procedurally or received over the network. Register it with
+/// [`AddAudioSource::add_audio_source`](crate::AddAudioSource::add_audio_source) and play it
+/// through the matching [`Audio<RawPcmSource>`](crate::Audio) resource.
+#[derive(Debug, Clone, TypeUuid)]
+#[uuid = "d57ffeac-5219-45fa-b074-a6b171505ec5"]
+pub struct RawPcmSource {
+    /// Samples in the range `[-1.0, 1.0]`, interleaved by channel (`L R L R ...` for stereo)
+    pub samples: Arc<[f32]>,
+    pub channels: u16,
+    pub sample_rate: u32,
+}
+
+impl RawPcmSource {
+    /// Creates a new [`RawPcmSource`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channels` or `sample_rate` is zero.
+    pub fn new(channels: u16, sample_rate: u32, samples: impl Into<Arc<[f32]>>) -> Self {
+        assert!(channels != 0, "RawPcmSource must have at least one channel");
+        assert!(
+            sample_rate != 0,
+            "RawPcmSource must have a non-zero sample rate"
+        );
+        Self {
+            samples: samples.into(),
+            channels,
+            sample_rate,
+        }
+    }
+}
+
 pub trait Decodable: Send + Sync + 'static {
     type Decoder;
 
@@ -47,3 +107,36 @@ impl Decodable for AudioSource {
         rodio::Decoder::new(Cursor::new(self.clone())).unwrap()
     }
 }
+
+impl Decodable for RawPcmSource {
+    type Decoder = SamplesBuffer<f32>;
+
+    fn decoder(&self) -> Self::Decoder {
+        SamplesBuffer::new(self.channels, self.sample_rate, self.samples.to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rodio::Source;
+
+    #[test]
+    fn malformed_bytes_are_rejected() {
+        assert!(AudioSource::try_from_bytes(vec![0u8; 64]).is_err());
+    }
+
+    #[test]
+    fn raw_pcm_decoder_yields_every_sample() {
+        let mono = RawPcmSource::new(1, 8_000, vec![0.25; 80]);
+        let decoder = mono.decoder();
+        assert_eq!(decoder.channels(), 1);
+        assert_eq!(decoder.sample_rate(), 8_000);
+        assert_eq!(decoder.count(), 80);
+
+        let stereo = RawPcmSource::new(2, 44_100, vec![0.5, -0.5, 0.25, -0.25]);
+        let decoder = stereo.decoder();
+        assert_eq!(decoder.channels(), 2);
+        assert_eq!(decoder.collect::<Vec<_>>(), vec![0.5, -0.5, 0.25, -0.25]);
+    }
+}
@@ -3,16 +3,37 @@ mod audio_output;
 mod audio_source;
 
 pub mod prelude {
-    pub use crate::{Audio, AudioOutput, AudioSource, Decodable};
+    pub use crate::{
+        AddAudioSource, Audio, AudioOutput, AudioSource, AudioSystem, Decodable, RawPcmSource,
+    };
 }
 
 pub use audio::*;
 pub use audio_output::*;
 pub use audio_source::*;
+/// The error returned by [AudioSource::try_from_bytes]
+pub use rodio::decoder::DecoderError;
+/// Output device types used by [AudioOutput::reinitialize]. Devices can be listed through
+/// `cpal::default_host().output_devices()`.
+pub use rodio::{cpal, Device, StreamError};
 
 use bevy_app::prelude::*;
-use bevy_asset::AddAsset;
-use bevy_ecs::system::IntoExclusiveSystem;
+use bevy_asset::{AddAsset, Asset};
+use bevy_ecs::{
+    schedule::{ExclusiveSystemDescriptorCoercion, SystemLabel},
+    system::IntoExclusiveSystem,
+};
+
+/// Labels for the systems added by [AudioPlugin] and [AddAudioSource::add_audio_source]
+///
+/// The playback systems are exclusive systems in [CoreStage::PostUpdate], so they run at the start
+/// of that stage, before any parallel systems in it. Other exclusive systems can be ordered
+/// relative to them with `.before(AudioSystem::PlayQueued)` or `.after(AudioSystem::PlayQueued)`.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemLabel)]
+pub enum AudioSystem {
+    /// Plays the sounds queued in every [Audio] resource
+    PlayQueued,
+}
 
 /// Adds support for audio playback to an App
 #[derive(Default)]
@@ -20,13 +41,37 @@ pub struct AudioPlugin;
 
 impl Plugin for AudioPlugin {
     fn build(&self, app: &mut AppBuilder) {
-        app.init_non_send_resource::<AudioOutput<AudioSource>>()
-            .add_asset::<AudioSource>()
-            .init_asset_loader::<Mp3Loader>()
-            .init_resource::<Audio<AudioSource>>()
+        app.add_audio_source::<AudioSource>()
+            .init_asset_loader::<Mp3Loader>();
+    }
+}
+
+/// [AppBuilder] extension methods for adding new [Decodable] audio source types
+pub trait AddAudioSource {
+    /// Registers `T` as an asset and adds the [Audio] and [AudioOutput] resources and the
+    /// playback system needed to play it.
+    fn add_audio_source<T>(&mut self) -> &mut Self
+    where
+        T: Asset + Decodable,
+        <T as Decodable>::Decoder: rodio::Source + Send + Sync,
+        <<T as Decodable>::Decoder as Iterator>::Item: rodio::Sample + Send + Sync;
+}
+
+impl AddAudioSource for AppBuilder {
+    fn add_audio_source<T>(&mut self) -> &mut Self
+    where
+        T: Asset + Decodable,
+        <T as Decodable>::Decoder: rodio::Source + Send + Sync,
+        <<T as Decodable>::Decoder as Iterator>::Item: rodio::Sample + Send + Sync,
+    {
+        self.init_non_send_resource::<AudioOutput<T>>()
+            .add_asset::<T>()
+            .init_resource::<Audio<T>>()
             .add_system_to_stage(
                 CoreStage::PostUpdate,
-                play_queued_audio_system::<AudioSource>.exclusive_system(),
-            );
+                play_queued_audio_system::<T>
+                    .exclusive_system()
+                    .label(AudioSystem::PlayQueued),
+            )
     }
 }
@@ -2,8 +2,8 @@ use super::CameraProjection;
 use bevy_ecs::{
     component::Component,
     entity::Entity,
-    event::EventReader,
-    query::Added,
+    event::{EventReader, EventWriter},
+    query::{Added, Changed},
     reflect::ReflectComponent,
     system::{Query, QuerySet, Res},
 };
@@ -22,6 +22,9 @@ pub struct Camera {
     pub window: WindowId,
     #[reflect(ignore)]
     pub depth_calculation: DepthCalculation,
+    /// The size of one physical pixel in world units when the view is snapped to whole pixels.
+    /// Set by [`camera_system`] from [`CameraProjection::pixel_snap_size`].
+    pub pixel_snap: Option<Vec2>,
 }
 
 #[derive(Debug, Clone, Copy, Reflect, Serialize, Deserialize)]
@@ -51,7 +54,7 @@ impl Camera {
         let window_size = Vec2::new(window.width(), window.height());
         // Build a transform to convert from world to NDC using camera data
         let world_to_ndc: Mat4 =
-            self.projection_matrix * camera_transform.compute_matrix().inverse();
+            self.projection_matrix * self.view_matrix(camera_transform).inverse();
         let ndc_space_coords: Vec3 = world_to_ndc.project_point3(world_position);
         // NDC z-values outside of 0 < z < 1 are behind the camera and are thus not in screen space
         if ndc_space_coords.z < 0.0 || ndc_space_coords.z > 1.0 {
@@ -61,15 +64,87 @@ impl Camera {
         let screen_space_coords = (ndc_space_coords.truncate() + Vec2::ONE) / 2.0 * window_size;
         Some(screen_space_coords)
     }
+
+    /// Returns the eight corners of the camera's view frustum in world space: the near plane's
+    /// bottom left, bottom right, top right and top left corners, followed by the far plane's in
+    /// the same order. Works for any projection, as it unprojects the corners of normalized
+    /// device coordinates through [`Camera::projection_matrix`].
+    pub fn world_frustum_corners(&self, camera_transform: &GlobalTransform) -> [Vec3; 8] {
+        let ndc_to_world = self.view_matrix(camera_transform) * self.projection_matrix.inverse();
+        let mut corners = [Vec3::ZERO; 8];
+        for (i, corner) in corners.iter_mut().enumerate() {
+            // wgpu's depth range is 0 (near) to 1 (far)
+            let z = if i < 4 { 0.0 } else { 1.0 };
+            let (x, y) = match i % 4 {
+                0 => (-1.0, -1.0),
+                1 => (1.0, -1.0),
+                2 => (1.0, 1.0),
+                _ => (-1.0, 1.0),
+            };
+            *corner = ndc_to_world.project_point3(Vec3::new(x, y, z));
+        }
+        corners
+    }
+
+    /// Like [`Camera::world_frustum_corners`], but for the slice of the frustum between the
+    /// distances `near` and `far` in front of the camera, such as a shadow cascade.
+    pub fn world_frustum_slice_corners(
+        &self,
+        camera_transform: &GlobalTransform,
+        near: f32,
+        far: f32,
+    ) -> [Vec3; 8] {
+        let view = self.view_matrix(camera_transform);
+        let world_to_view = view.inverse();
+        let corners = self.world_frustum_corners(camera_transform);
+        let mut slice = [Vec3::ZERO; 8];
+        for i in 0..4 {
+            // interpolate along each frustum edge by the distance along the view direction
+            let near_corner = world_to_view.transform_point3(corners[i]);
+            let far_corner = world_to_view.transform_point3(corners[i + 4]);
+            let (near_depth, far_depth) = (-near_corner.z, -far_corner.z);
+            let at_depth = |depth: f32| {
+                let t = (depth - near_depth) / (far_depth - near_depth);
+                view.transform_point3(near_corner.lerp(far_corner, t))
+            };
+            slice[i] = at_depth(near);
+            slice[i + 4] = at_depth(far);
+        }
+        slice
+    }
+
+    /// Computes the camera's view matrix (camera to world), snapping its translation to whole
+    /// pixels if [`Camera::pixel_snap`] is set.
+    pub fn view_matrix(&self, camera_transform: &GlobalTransform) -> Mat4 {
+        match self.pixel_snap {
+            Some(pixel_size) => {
+                let mut camera_transform = *camera_transform;
+                let translation = camera_transform.translation;
+                let snapped = (translation.truncate() / pixel_size).round() * pixel_size;
+                camera_transform.translation = snapped.extend(translation.z);
+                camera_transform.compute_matrix()
+            }
+            None => camera_transform.compute_matrix(),
+        }
+    }
+}
+
+/// An event that is sent whenever a [`Camera`]'s projection matrix changes, either because its
+/// window was resized or because its projection component was modified.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CameraProjectionChanged {
+    pub entity: Entity,
 }
 
 pub fn camera_system<T: CameraProjection + Component>(
     mut window_resized_events: EventReader<WindowResized>,
     mut window_created_events: EventReader<WindowCreated>,
+    mut projection_changed_events: EventWriter<CameraProjectionChanged>,
     windows: Res<Windows>,
     mut queries: QuerySet<(
         Query<(Entity, &mut Camera, &mut T)>,
         Query<Entity, Added<Camera>>,
+        Query<Entity, Changed<T>>,
     )>,
 ) {
     let mut changed_window_ids = Vec::new();
@@ -97,13 +172,136 @@ pub fn camera_system<T: CameraProjection + Component>(
     for entity in &mut queries.q1().iter() {
         added_cameras.push(entity);
     }
+    let mut changed_projections = vec![];
+    for entity in &mut queries.q2().iter() {
+        changed_projections.push(entity);
+    }
     for (entity, mut camera, mut camera_projection) in queries.q0_mut().iter_mut() {
         if let Some(window) = windows.get(camera.window) {
-            if changed_window_ids.contains(&window.id()) || added_cameras.contains(&entity) {
+            if changed_window_ids.contains(&window.id())
+                || added_cameras.contains(&entity)
+                || changed_projections.contains(&entity)
+            {
                 camera_projection.update(window.width(), window.height());
-                camera.projection_matrix = camera_projection.get_projection_matrix();
+                let projection_matrix = camera_projection.get_projection_matrix();
+                if camera.projection_matrix != projection_matrix {
+                    camera.projection_matrix = projection_matrix;
+                    projection_changed_events.send(CameraProjectionChanged { entity });
+                }
                 camera.depth_calculation = camera_projection.depth_calculation();
+                camera.pixel_snap = camera_projection.pixel_snap_size(
+                    window.physical_width() as f32,
+                    window.physical_height() as f32,
+                );
             }
         }
     }
 }
+
+#[cfg(test)]
+#[allow(clippy::float_cmp)]
+mod tests {
+    use super::{camera_system, Camera, CameraProjection, CameraProjectionChanged};
+    use crate::camera::{OrthographicProjection, ScalingMode};
+    use bevy_ecs::{
+        event::{Events, ManualEventReader},
+        schedule::{Stage, SystemStage},
+        system::IntoSystem,
+        world::World,
+    };
+    use bevy_math::{Vec2, Vec3};
+    use bevy_transform::components::GlobalTransform;
+    use bevy_window::{Window, WindowCreated, WindowDescriptor, WindowId, WindowResized, Windows};
+
+    #[test]
+    fn pixel_snapped_view_is_pixel_aligned() {
+        // a 1.5 scale factor makes a physical pixel 2/3 of a world unit
+        let pixel_size = Vec2::splat(2.0 / 3.0);
+        let camera = Camera {
+            pixel_snap: Some(pixel_size),
+            ..Default::default()
+        };
+        let transform = GlobalTransform::from_translation(Vec3::new(10.3, -4.9, 5.0));
+
+        let translation = camera.view_matrix(&transform).w_axis.truncate();
+        let pixels = translation.truncate() / pixel_size;
+        assert!((pixels - pixels.round()).abs().max_element() < 1e-4);
+        assert_eq!(translation.z, 5.0);
+
+        let unsnapped = Camera::default().view_matrix(&transform).w_axis.truncate();
+        assert_eq!(unsnapped, transform.translation);
+    }
+
+    #[test]
+    fn projection_changed_event_is_sent_once() {
+        let mut world = World::default();
+        let mut windows = Windows::default();
+        windows.add(Window::new(
+            WindowId::primary(),
+            &WindowDescriptor::default(),
+            800,
+            600,
+            1.0,
+            None,
+        ));
+        world.insert_resource(windows);
+        world.insert_resource(Events::<WindowResized>::default());
+        world.insert_resource(Events::<WindowCreated>::default());
+        world.insert_resource(Events::<CameraProjectionChanged>::default());
+        let entity = world
+            .spawn()
+            .insert(Camera::default())
+            .insert(OrthographicProjection::default())
+            .id();
+
+        let mut stage = SystemStage::single(camera_system::<OrthographicProjection>.system());
+        let mut reader = ManualEventReader::<CameraProjectionChanged>::default();
+        let mut run = |world: &mut World| {
+            stage.run(world);
+            let events = world
+                .get_resource::<Events<CameraProjectionChanged>>()
+                .unwrap();
+            reader.iter(events).copied().collect::<Vec<_>>()
+        };
+
+        // the projection of a new camera is computed from its window
+        assert_eq!(run(&mut world), vec![CameraProjectionChanged { entity }]);
+        assert_eq!(run(&mut world), vec![]);
+
+        world
+            .get_mut::<OrthographicProjection>(entity)
+            .unwrap()
+            .scaling_mode = ScalingMode::FixedVertical;
+        assert_eq!(run(&mut world), vec![CameraProjectionChanged { entity }]);
+        assert_eq!(run(&mut world), vec![]);
+    }
+
+    #[test]
+    fn orthographic_frustum_corners() {
+        let projection = OrthographicProjection::default();
+        let camera = Camera {
+            projection_matrix: projection.get_projection_matrix(),
+            ..Default::default()
+        };
+        let transform = GlobalTransform::from_translation(Vec3::new(10.0, 0.0, 0.0));
+
+        let expected = [
+            Vec3::new(9.0, -1.0, 0.0),
+            Vec3::new(11.0, -1.0, 0.0),
+            Vec3::new(11.0, 1.0, 0.0),
+            Vec3::new(9.0, 1.0, 0.0),
+            Vec3::new(9.0, -1.0, -1000.0),
+            Vec3::new(11.0, -1.0, -1000.0),
+            Vec3::new(11.0, 1.0, -1000.0),
+            Vec3::new(9.0, 1.0, -1000.0),
+        ];
+        let corners = camera.world_frustum_corners(&transform);
+        for (corner, expected) in corners.iter().zip(expected.iter()) {
+            assert!((*corner - *expected).abs().max_element() < 1e-3);
+        }
+
+        let slice = camera.world_frustum_slice_corners(&transform, 10.0, 20.0);
+        assert!((slice[0] - Vec3::new(9.0, -1.0, -10.0)).abs().max_element() < 1e-3);
+        assert!((slice[6] - Vec3::new(11.0, 1.0, -20.0)).abs().max_element() < 1e-3);
+    }
+}
@@ -0,0 +1,89 @@
+use bevy_core::Time;
+use bevy_ecs::{
+    entity::Entity,
+    system::{Local, Query, Res},
+};
+use bevy_math::Vec3;
+use bevy_transform::components::{GlobalTransform, Parent, Transform};
+use bevy_utils::HashMap;
+
+/// Moves a camera's [`Transform`] smoothly towards a target entity every frame
+///
+/// Only the camera's translation is changed; its rotation is left alone. If the target no longer
+/// exists, the camera stays where it is until the target is replaced. The camera may be the child
+/// of another entity, in which case it moves to the same world position as a root camera would.
+#[derive(Debug, Clone)]
+pub struct CameraFollow {
+    /// The entity to follow. Its [`GlobalTransform`] from the previous transform propagation is
+    /// used, so the camera trails a moving target by up to one frame.
+    pub target: Entity,
+    /// Offset from the target's position that the camera moves towards
+    pub offset: Vec3,
+    /// Time constant in seconds: the camera covers about 63% of the remaining distance in this
+    /// time, independently of the frame rate. `0.0` snaps the camera to its destination.
+    pub smoothing: f32,
+    /// When set, the destination is moved ahead of the target by its velocity multiplied by this
+    /// (per axis, in seconds), so the camera shows more of where the target is going.
+    pub lookahead: Option<Vec3>,
+}
+
+impl CameraFollow {
+    /// Follows `target` from `offset`, snapping to it every frame.
+    pub fn new(target: Entity, offset: Vec3) -> Self {
+        Self {
+            target,
+            offset,
+            smoothing: 0.0,
+            lookahead: None,
+        }
+    }
+}
+
+/// Moves cameras with a [`CameraFollow`] towards their target
+///
+/// The target each camera followed last frame and where it was are kept to compute the target's
+/// velocity for [`CameraFollow::lookahead`]. Switching to another target skips the lookahead for
+/// one frame instead of treating the jump between the two targets as movement.
+pub fn camera_follow_system(
+    time: Res<Time>,
+    mut last_target_positions: Local<HashMap<Entity, (Entity, Vec3)>>,
+    targets: Query<&GlobalTransform>,
+    mut cameras: Query<(Entity, &CameraFollow, &mut Transform, Option<&Parent>)>,
+) {
+    let delta = time.delta_seconds();
+    // rebuilt every frame, so cameras that were despawned or stopped following are dropped
+    let mut target_positions = HashMap::default();
+    for (entity, follow, mut transform, parent) in cameras.iter_mut() {
+        let target_position = match targets.get(follow.target) {
+            Ok(target_transform) => target_transform.translation,
+            Err(_) => continue,
+        };
+
+        let mut destination = target_position + follow.offset;
+        if let (Some(lookahead), Some((last_target, last_position))) =
+            (follow.lookahead, last_target_positions.get(&entity))
+        {
+            if delta > 0.0 && *last_target == follow.target {
+                let velocity = (target_position - *last_position) / delta;
+                destination += velocity * lookahead;
+            }
+        }
+        target_positions.insert(entity, (follow.target, target_position));
+
+        // the destination is in world space, but the translation is relative to the parent
+        if let Some(parent_transform) = parent.and_then(|parent| targets.get(parent.0).ok()) {
+            destination = parent_transform
+                .compute_matrix()
+                .inverse()
+                .transform_point3(destination);
+        }
+
+        transform.translation = if follow.smoothing > 0.0 {
+            let t = 1.0 - (-delta / follow.smoothing).exp();
+            transform.translation.lerp(destination, t)
+        } else {
+            destination
+        };
+    }
+    *last_target_positions = target_positions;
+}
@@ -1,13 +1,20 @@
 use super::DepthCalculation;
 use bevy_ecs::reflect::ReflectComponent;
-use bevy_math::Mat4;
+use bevy_math::{Mat4, Vec2, Vec3};
 use bevy_reflect::{Reflect, ReflectDeserialize};
+use bevy_transform::components::Transform;
 use serde::{Deserialize, Serialize};
 
 pub trait CameraProjection {
     fn get_projection_matrix(&self) -> Mat4;
     fn update(&mut self, width: f32, height: f32);
     fn depth_calculation(&self) -> DepthCalculation;
+
+    /// The size of one physical pixel in world units, if the camera's view should be snapped to
+    /// whole pixels. Called with the physical size of the camera's window.
+    fn pixel_snap_size(&self, _physical_width: f32, _physical_height: f32) -> Option<Vec2> {
+        None
+    }
 }
 
 #[derive(Debug, Clone, Reflect)]
@@ -79,6 +86,10 @@ pub struct OrthographicProjection {
     pub scaling_mode: ScalingMode,
     pub scale: f32,
     pub depth_calculation: DepthCalculation,
+    /// Snaps the camera's view translation to whole physical pixels when rendering, which avoids
+    /// shimmering in pixel art. The camera's [`Transform`](bevy_transform::components::Transform)
+    /// keeps its exact position. Assumes the camera is not rotated around its view axis.
+    pub pixel_perfect: bool,
 }
 
 impl CameraProjection for OrthographicProjection {
@@ -144,6 +155,60 @@ impl CameraProjection for OrthographicProjection {
     fn depth_calculation(&self) -> DepthCalculation {
         self.depth_calculation
     }
+
+    fn pixel_snap_size(&self, physical_width: f32, physical_height: f32) -> Option<Vec2> {
+        if !self.pixel_perfect || physical_width <= 0.0 || physical_height <= 0.0 {
+            return None;
+        }
+        Some(Vec2::new(
+            (self.right - self.left) * self.scale / physical_width,
+            (self.top - self.bottom) * self.scale / physical_height,
+        ))
+    }
+}
+
+impl OrthographicProjection {
+    /// Adjusts [`scale`](Self::scale) and moves `camera_transform` within its view plane so every
+    /// point in `points` is visible, with at least `padding` world units around them.
+    ///
+    /// The camera keeps its rotation and depth, and the projection's aspect ratio is unchanged,
+    /// so the tighter axis gets extra space. The projection's bounds must already be set, which
+    /// [`camera_system`](super::camera_system) does once the window exists. A single point with
+    /// no padding only centers the camera on it, and an empty slice changes nothing. Points
+    /// outside the `near`/`far` range are not accounted for.
+    pub fn fit_to_points(
+        &mut self,
+        camera_transform: &mut Transform,
+        points: &[Vec3],
+        padding: f32,
+    ) {
+        if points.is_empty() {
+            return;
+        }
+
+        // bounds of the points in the camera's local space
+        let world_to_camera = camera_transform.compute_matrix().inverse();
+        let (mut min, mut max) = (Vec2::splat(f32::MAX), Vec2::splat(f32::MIN));
+        for point in points {
+            let local = world_to_camera.transform_point3(*point).truncate();
+            min = min.min(local);
+            max = max.max(local);
+        }
+        min -= Vec2::splat(padding);
+        max += Vec2::splat(padding);
+
+        let view_size = Vec2::new(self.right - self.left, self.top - self.bottom);
+        let fit_size = max - min;
+        if view_size.min_element() > 0.0 && fit_size.max_element() > 0.0 {
+            self.scale = (fit_size / view_size).max_element();
+        }
+
+        let view_center =
+            Vec2::new(self.left + self.right, self.bottom + self.top) * 0.5 * self.scale;
+        let offset = ((min + max) * 0.5 - view_center).extend(0.0);
+        camera_transform.translation +=
+            camera_transform.rotation * (camera_transform.scale * offset);
+    }
 }
 
 impl Default for OrthographicProjection {
@@ -159,6 +224,49 @@ impl Default for OrthographicProjection {
             scaling_mode: ScalingMode::WindowSize,
             scale: 1.0,
             depth_calculation: DepthCalculation::Distance,
+            pixel_perfect: false,
         }
     }
 }
+
+#[cfg(test)]
+#[allow(clippy::float_cmp)]
+mod tests {
+    use super::{CameraProjection, OrthographicProjection};
+    use bevy_math::Vec3;
+    use bevy_transform::components::Transform;
+
+    #[test]
+    fn orthographic_fit_to_points() {
+        let mut projection = OrthographicProjection::default();
+        projection.update(800.0, 600.0);
+        let mut transform = Transform::from_xyz(100.0, 100.0, 500.0);
+        let points = [
+            Vec3::new(-300.0, 20.0, 0.0),
+            Vec3::new(250.0, 900.0, 0.0),
+            Vec3::new(40.0, -60.0, 10.0),
+        ];
+
+        projection.fit_to_points(&mut transform, &points, 10.0);
+
+        let view_projection =
+            projection.get_projection_matrix() * transform.compute_matrix().inverse();
+        let mut touches_edge = false;
+        for point in points.iter() {
+            let ndc = view_projection.project_point3(*point).truncate();
+            assert!(ndc.abs().max_element() < 1.0);
+            touches_edge |= ndc.abs().max_element() > 0.95;
+        }
+        assert!(touches_edge);
+        assert_eq!(transform.translation.z, 500.0);
+
+        // a single point without padding only moves the camera
+        let scale = projection.scale;
+        projection.fit_to_points(&mut transform, &[Vec3::new(7.0, 8.0, 0.0)], 0.0);
+        assert_eq!(projection.scale, scale);
+        assert!((transform.translation - Vec3::new(7.0, 8.0, 500.0)).length() < 1e-3);
+
+        projection.fit_to_points(&mut transform, &[], 10.0);
+        assert_eq!(projection.scale, scale);
+    }
+}
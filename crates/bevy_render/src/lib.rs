@@ -160,6 +160,7 @@ impl Plugin for RenderPlugin {
         .init_resource::<RenderResourceBindings>()
         .init_resource::<AssetRenderResourceBindings>()
         .init_resource::<ActiveCameras>()
+        .add_event::<camera::CameraProjectionChanged>()
         .add_system_to_stage(CoreStage::PreUpdate, draw::clear_draw_system.system())
         .add_system_to_stage(
             CoreStage::PostUpdate,
@@ -177,6 +178,12 @@ impl Plugin for RenderPlugin {
                 .system()
                 .before(RenderSystem::VisibleEntities),
         )
+        .add_system_to_stage(
+            CoreStage::PostUpdate,
+            camera::camera_follow_system
+                .system()
+                .before(TransformSystem::TransformPropagate),
+        )
         .add_system_to_stage(
             CoreStage::PostUpdate,
             camera::visible_entities_system
@@ -158,7 +158,7 @@ pub fn camera_node_system(
         );
     }
 
-    let view = global_transform.compute_matrix();
+    let view = camera.view_matrix(global_transform);
     let mut offset = 0;
 
     if let Some(RenderResourceBinding::Buffer { buffer, .. }) = bindings.get(CAMERA_VIEW) {
@@ -1,5 +1,6 @@
 use bevy_math::{Mat4, Vec3};
 use bevy_render::{
+    color::Color,
     draw::{Draw, DrawContext, DrawError, Drawable},
     mesh,
     mesh::Mesh,
@@ -13,11 +14,23 @@ use bevy_utils::tracing::error;
 use crate::{PositionedGlyph, TextSection};
 use bevy_render::pipeline::IndexFormat;
 
+/// The color of `glyph`: its entry in `per_glyph_colors`, or else its section's color
+pub(crate) fn glyph_color(
+    glyph: &PositionedGlyph,
+    sections: &[TextSection],
+    per_glyph_colors: Option<&[Color]>,
+) -> Color {
+    per_glyph_colors
+        .and_then(|colors| colors.get(glyph.char_index).copied())
+        .unwrap_or(sections[glyph.section_index].style.color)
+}
+
 pub struct DrawableText<'a> {
     pub render_resource_bindings: &'a mut RenderResourceBindings,
     pub position: Vec3,
     pub scale_factor: f32,
     pub sections: &'a [TextSection],
+    pub per_glyph_colors: Option<&'a [Color]>,
     pub text_glyphs: &'a Vec<PositionedGlyph>,
     pub msaa: &'a Msaa,
     pub font_quad_vertex_layout: &'a VertexBufferLayout,
@@ -71,7 +84,7 @@ impl<'a> Drawable for DrawableText<'a> {
 
             let sprite = TextureAtlasSprite {
                 index: tv.atlas_info.glyph_index,
-                color: self.sections[tv.section_index].style.color,
+                color: glyph_color(tv, self.sections, self.per_glyph_colors),
                 flip_x: false,
                 flip_y: false,
             };
@@ -8,6 +8,30 @@ pub struct Font {
     pub font: FontArc,
 }
 
+/// How glyph outlines are turned into pixels, set per section with
+/// [`TextStyle::rasterization`](crate::TextStyle::rasterization)
+///
+/// Each mode gets its own font atlases, so the same font can be drawn in several modes. Glyphs
+/// are never hinted, whatever the mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RasterizationMode {
+    /// Partially covered pixels get partial opacity, and atlases are sampled linearly.
+    Antialiased,
+    /// Pixels are either fully opaque or fully transparent, depending on whether at least half of
+    /// each pixel is covered.
+    Mono,
+    /// Like [`RasterizationMode::Mono`], and the font's atlases are also sampled with nearest
+    /// filtering, so pixel fonts stay crisp when scaled. Pair it with
+    /// [`GlyphPositioning::PixelSnapped`](crate::GlyphPositioning::PixelSnapped).
+    Pixelated,
+}
+
+impl Default for RasterizationMode {
+    fn default() -> Self {
+        RasterizationMode::Antialiased
+    }
+}
+
 impl Font {
     pub fn try_from_bytes(font_data: Vec<u8>) -> Result<Self, InvalidFont> {
         let font = FontVec::try_from_vec(font_data)?;
@@ -15,13 +39,25 @@ impl Font {
         Ok(Font { font })
     }
 
-    pub fn get_outlined_glyph_texture(outlined_glyph: OutlinedGlyph) -> Texture {
+    pub fn get_outlined_glyph_texture(
+        outlined_glyph: OutlinedGlyph,
+        rasterization: RasterizationMode,
+    ) -> Texture {
         let bounds = outlined_glyph.px_bounds();
         let width = bounds.width() as usize;
         let height = bounds.height() as usize;
         let mut alpha = vec![0.0; width * height];
         outlined_glyph.draw(|x, y, v| {
-            alpha[y as usize * width + x as usize] = v;
+            alpha[y as usize * width + x as usize] = match rasterization {
+                RasterizationMode::Antialiased => v,
+                RasterizationMode::Mono | RasterizationMode::Pixelated => {
+                    if v >= 0.5 {
+                        1.0
+                    } else {
+                        0.0
+                    }
+                }
+            };
         });
 
         // TODO: make this texture grayscale
@@ -1,10 +1,12 @@
 use ab_glyph::{GlyphId, Point};
 use bevy_asset::{Assets, Handle};
 use bevy_math::Vec2;
-use bevy_render::texture::{Extent3d, Texture, TextureDimension, TextureFormat};
+use bevy_render::texture::{Extent3d, FilterMode, Texture, TextureDimension, TextureFormat};
 use bevy_sprite::{DynamicTextureAtlasBuilder, TextureAtlas};
 use bevy_utils::HashMap;
 
+use crate::RasterizationMode;
+
 #[cfg(feature = "subpixel_glyph_atlas")]
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
 pub struct SubpixelOffset {
@@ -47,13 +49,19 @@ impl FontAtlas {
         textures: &mut Assets<Texture>,
         texture_atlases: &mut Assets<TextureAtlas>,
         size: Vec2,
+        rasterization: RasterizationMode,
     ) -> FontAtlas {
-        let atlas_texture = textures.add(Texture::new_fill(
+        let mut atlas_texture = Texture::new_fill(
             Extent3d::new(size.x as u32, size.y as u32, 1),
             TextureDimension::D2,
             &[0, 0, 0, 0],
             TextureFormat::Rgba8UnormSrgb,
-        ));
+        );
+        if rasterization == RasterizationMode::Pixelated {
+            atlas_texture.sampler.mag_filter = FilterMode::Nearest;
+            atlas_texture.sampler.min_filter = FilterMode::Nearest;
+        }
+        let atlas_texture = textures.add(atlas_texture);
         let texture_atlas = TextureAtlas::new_empty(atlas_texture, size);
         Self {
             texture_atlas: texture_atlases.add(texture_atlas),
@@ -1,19 +1,27 @@
-use crate::{error::TextError, Font, FontAtlas};
-use ab_glyph::{GlyphId, OutlinedGlyph, Point};
+use crate::{
+    error::TextError, glyph_brush::NOTDEF, Font, FontAtlas, RasterizationMode, TextSettings,
+};
+use ab_glyph::{Font as _, Glyph, GlyphId, OutlinedGlyph, Point, ScaleFont as _};
 use bevy_asset::{Assets, Handle};
 use bevy_core::FloatOrd;
+use bevy_ecs::{
+    system::{Command, Commands},
+    world::World,
+};
 use bevy_math::Vec2;
 use bevy_reflect::TypeUuid;
 use bevy_render::texture::Texture;
-use bevy_sprite::TextureAtlas;
-use bevy_utils::HashMap;
+use bevy_sprite::{Rect, TextureAtlas};
+use bevy_utils::{tracing::warn, HashMap};
 
-type FontSizeKey = FloatOrd;
+/// Font size and rasterization mode. Each combination gets its own atlases, since the mode
+/// changes both the rasterized glyphs and the atlas sampler.
+type FontAtlasKey = (FloatOrd, RasterizationMode);
 
 #[derive(TypeUuid)]
 #[uuid = "73ba778b-b6b5-4f45-982d-d21b6b86ace2"]
 pub struct FontAtlasSet {
-    font_atlases: HashMap<FontSizeKey, Vec<FontAtlas>>,
+    font_atlases: HashMap<FontAtlasKey, Vec<FontAtlas>>,
 }
 
 #[derive(Debug, Clone)]
@@ -22,6 +30,32 @@ pub struct GlyphAtlasInfo {
     pub glyph_index: u32,
 }
 
+/// Metrics of a single glyph, along with where it is stored in a font atlas
+#[derive(Debug, Clone)]
+pub struct GlyphInfo {
+    /// Horizontal distance in pixels to advance the pen position after this glyph, not including
+    /// kerning
+    pub advance: f32,
+    /// Horizontal distance in pixels from the pen position to the left edge of the glyph
+    pub left_side_bearing: f32,
+    /// Where the glyph is stored in the font atlas, or `None` for glyphs without an outline, such
+    /// as spaces
+    pub atlas: Option<GlyphAtlasLocation>,
+}
+
+/// The location of a rasterized glyph in a font atlas
+#[derive(Debug, Clone)]
+pub struct GlyphAtlasLocation {
+    pub atlas_info: GlyphAtlasInfo,
+    /// The atlas texture containing the glyph
+    pub texture: Handle<Texture>,
+    /// Normalized texture coordinates of the glyph within `texture`
+    pub uv_rect: Rect,
+    /// Bounds in pixels of the rasterized glyph relative to the pen position on the baseline,
+    /// with y pointing down
+    pub px_bounds: Rect,
+}
+
 impl Default for FontAtlasSet {
     fn default() -> Self {
         FontAtlasSet {
@@ -31,13 +65,19 @@ impl Default for FontAtlasSet {
 }
 
 impl FontAtlasSet {
-    pub fn iter(&self) -> impl Iterator<Item = (&FontSizeKey, &Vec<FontAtlas>)> {
+    pub fn iter(&self) -> impl Iterator<Item = (&FontAtlasKey, &Vec<FontAtlas>)> {
         self.font_atlases.iter()
     }
 
-    pub fn has_glyph(&self, glyph_id: GlyphId, glyph_position: Point, font_size: f32) -> bool {
+    pub fn has_glyph(
+        &self,
+        glyph_id: GlyphId,
+        glyph_position: Point,
+        font_size: f32,
+        rasterization: RasterizationMode,
+    ) -> bool {
         self.font_atlases
-            .get(&FloatOrd(font_size))
+            .get(&(FloatOrd(font_size), rasterization))
             .map_or(false, |font_atlas| {
                 font_atlas
                     .iter()
@@ -45,11 +85,31 @@ impl FontAtlasSet {
             })
     }
 
+    /// Returns the number of atlas pages allocated across all font sizes and rasterization modes.
+    pub fn atlas_count(&self) -> usize {
+        self.font_atlases.values().map(Vec::len).sum()
+    }
+
+    /// Returns the number of bytes of texture memory used by the atlas pages of this set.
+    pub fn texture_memory_usage(&self, texture_atlases: &Assets<TextureAtlas>) -> usize {
+        self.font_atlases
+            .values()
+            .flatten()
+            .filter_map(|atlas| texture_atlases.get(&atlas.texture_atlas))
+            .map(|texture_atlas| {
+                // font atlases are always Rgba8UnormSrgb, i.e. 4 bytes per pixel
+                texture_atlas.size.x as usize * texture_atlas.size.y as usize * 4
+            })
+            .sum()
+    }
+
     pub fn add_glyph_to_atlas(
         &mut self,
         texture_atlases: &mut Assets<TextureAtlas>,
         textures: &mut Assets<Texture>,
         outlined_glyph: OutlinedGlyph,
+        rasterization: RasterizationMode,
+        text_settings: &TextSettings,
     ) -> Result<GlyphAtlasInfo, TextError> {
         let glyph = outlined_glyph.glyph();
         let glyph_id = glyph.id;
@@ -57,15 +117,16 @@ impl FontAtlasSet {
         let font_size = glyph.scale.y;
         let font_atlases = self
             .font_atlases
-            .entry(FloatOrd(font_size))
+            .entry((FloatOrd(font_size), rasterization))
             .or_insert_with(|| {
                 vec![FontAtlas::new(
                     textures,
                     texture_atlases,
-                    Vec2::new(512.0, 512.0),
+                    text_settings.font_atlas_size,
+                    rasterization,
                 )]
             });
-        let glyph_texture = Font::get_outlined_glyph_texture(outlined_glyph);
+        let glyph_texture = Font::get_outlined_glyph_texture(outlined_glyph, rasterization);
         let add_char_to_font_atlas = |atlas: &mut FontAtlas| -> bool {
             atlas.add_glyph(
                 textures,
@@ -79,8 +140,18 @@ impl FontAtlasSet {
             font_atlases.push(FontAtlas::new(
                 textures,
                 texture_atlases,
-                Vec2::new(512.0, 512.0),
+                text_settings.font_atlas_size,
+                rasterization,
             ));
+            if font_atlases.len() == text_settings.soft_max_font_atlases + 1 {
+                warn!(
+                    "Font size {} now uses {} font atlases, more than the soft maximum of {}. \
+                    Consider increasing `TextSettings::font_atlas_size`.",
+                    font_size,
+                    font_atlases.len(),
+                    text_settings.soft_max_font_atlases,
+                );
+            }
             if !font_atlases.last_mut().unwrap().add_glyph(
                 textures,
                 texture_atlases,
@@ -93,18 +164,150 @@ impl FontAtlasSet {
         }
 
         Ok(self
-            .get_glyph_atlas_info(font_size, glyph_id, glyph_position)
+            .get_glyph_atlas_info(font_size, glyph_id, glyph_position, rasterization)
             .unwrap())
     }
 
+    /// Rasterizes `chars` into this set ahead of time, so that the first frame showing them
+    /// doesn't stall while their glyphs are added to the atlas.
+    ///
+    /// `font_size` is in physical pixels, i.e. [`TextStyle::font_size`](crate::TextStyle)
+    /// multiplied by the window's scale factor. Characters without an outline, such as spaces,
+    /// are skipped. With the `subpixel_glyph_atlas` feature only the pixel-aligned rasterization
+    /// is preloaded.
+    #[allow(clippy::too_many_arguments)]
+    pub fn preload(
+        &mut self,
+        font: &Font,
+        font_size: f32,
+        rasterization: RasterizationMode,
+        chars: impl IntoIterator<Item = char>,
+        texture_atlases: &mut Assets<TextureAtlas>,
+        textures: &mut Assets<Texture>,
+        text_settings: &TextSettings,
+    ) -> Result<(), TextError> {
+        for c in chars {
+            let glyph = font
+                .font
+                .glyph_id(c)
+                .with_scale_and_position(font_size, ab_glyph::point(0.0, 0.0));
+            self.get_or_add_glyph(
+                font,
+                glyph,
+                rasterization,
+                texture_atlases,
+                textures,
+                text_settings,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Returns the metrics of `c` at `font_size` (in physical pixels) and its location in the
+    /// font atlas for `rasterization`, rasterizing it first if needed. Returns `Ok(None)` if `font` has no glyph for
+    /// `c`.
+    ///
+    /// This exposes the data the atlas already manages, so custom layout code can place glyphs
+    /// without rasterizing them a second time.
+    #[allow(clippy::too_many_arguments)]
+    pub fn glyph_info(
+        &mut self,
+        font: &Font,
+        font_size: f32,
+        rasterization: RasterizationMode,
+        c: char,
+        texture_atlases: &mut Assets<TextureAtlas>,
+        textures: &mut Assets<Texture>,
+        text_settings: &TextSettings,
+    ) -> Result<Option<GlyphInfo>, TextError> {
+        let glyph_id = font.font.glyph_id(c);
+        if glyph_id == NOTDEF {
+            return Ok(None);
+        }
+        let scaled_font = font.font.as_scaled(font_size);
+        let glyph = glyph_id.with_scale_and_position(font_size, ab_glyph::point(0.0, 0.0));
+        let px_bounds = font
+            .font
+            .outline_glyph(glyph.clone())
+            .map(|outlined_glyph| {
+                let bounds = outlined_glyph.px_bounds();
+                Rect {
+                    min: Vec2::new(bounds.min.x, bounds.min.y),
+                    max: Vec2::new(bounds.max.x, bounds.max.y),
+                }
+            });
+
+        let atlas_info = self.get_or_add_glyph(
+            font,
+            glyph,
+            rasterization,
+            texture_atlases,
+            textures,
+            text_settings,
+        )?;
+        let atlas = atlas_info
+            .zip(px_bounds)
+            .and_then(|(atlas_info, px_bounds)| {
+                let texture_atlas = texture_atlases.get(&atlas_info.texture_atlas)?;
+                let rect = texture_atlas.textures[atlas_info.glyph_index as usize];
+                Some(GlyphAtlasLocation {
+                    texture: texture_atlas.texture.clone_weak(),
+                    uv_rect: Rect {
+                        min: rect.min / texture_atlas.size,
+                        max: rect.max / texture_atlas.size,
+                    },
+                    px_bounds,
+                    atlas_info,
+                })
+            });
+
+        Ok(Some(GlyphInfo {
+            advance: scaled_font.h_advance(glyph_id),
+            left_side_bearing: scaled_font.h_side_bearing(glyph_id),
+            atlas,
+        }))
+    }
+
+    /// Returns the atlas info of `glyph`, adding it to the atlas first if it isn't there yet.
+    /// Returns `Ok(None)` for glyphs without an outline.
+    fn get_or_add_glyph(
+        &mut self,
+        font: &Font,
+        glyph: Glyph,
+        rasterization: RasterizationMode,
+        texture_atlases: &mut Assets<TextureAtlas>,
+        textures: &mut Assets<Texture>,
+        text_settings: &TextSettings,
+    ) -> Result<Option<GlyphAtlasInfo>, TextError> {
+        let font_size = glyph.scale.y;
+        if let Some(atlas_info) =
+            self.get_glyph_atlas_info(font_size, glyph.id, glyph.position, rasterization)
+        {
+            return Ok(Some(atlas_info));
+        }
+        font.font
+            .outline_glyph(glyph)
+            .map(|outlined_glyph| {
+                self.add_glyph_to_atlas(
+                    texture_atlases,
+                    textures,
+                    outlined_glyph,
+                    rasterization,
+                    text_settings,
+                )
+            })
+            .transpose()
+    }
+
     pub fn get_glyph_atlas_info(
         &self,
         font_size: f32,
         glyph_id: GlyphId,
         position: Point,
+        rasterization: RasterizationMode,
     ) -> Option<GlyphAtlasInfo> {
         self.font_atlases
-            .get(&FloatOrd(font_size))
+            .get(&(FloatOrd(font_size), rasterization))
             .and_then(|font_atlases| {
                 font_atlases
                     .iter()
@@ -120,3 +323,178 @@ impl FontAtlasSet {
             })
     }
 }
+
+/// A [`Command`] that rasterizes a set of characters into the atlas of a font ahead of time
+///
+/// See [`FontAtlasSet::preload`]. The font must have finished loading when the command is
+/// applied; otherwise a warning is logged and nothing is preloaded.
+pub struct PreloadGlyphs {
+    pub font: Handle<Font>,
+    /// Font size in physical pixels
+    pub font_size: f32,
+    pub rasterization: RasterizationMode,
+    pub chars: String,
+}
+
+impl Command for PreloadGlyphs {
+    fn write(self: Box<Self>, world: &mut World) {
+        let world = world.cell();
+        let fonts = world.get_resource::<Assets<Font>>().unwrap();
+        let font = match fonts.get(&self.font) {
+            Some(font) => font,
+            None => {
+                warn!(
+                    "Could not preload glyphs for font {:?}: the font has not been loaded yet",
+                    self.font
+                );
+                return;
+            }
+        };
+        let text_settings = world.get_resource::<TextSettings>().unwrap();
+        let mut font_atlas_set_storage = world.get_resource_mut::<Assets<FontAtlasSet>>().unwrap();
+        let mut texture_atlases = world.get_resource_mut::<Assets<TextureAtlas>>().unwrap();
+        let mut textures = world.get_resource_mut::<Assets<Texture>>().unwrap();
+
+        let font_atlas_set = font_atlas_set_storage
+            .get_or_insert_with(self.font.as_weak::<FontAtlasSet>(), FontAtlasSet::default);
+        if let Err(e) = font_atlas_set.preload(
+            font,
+            self.font_size,
+            self.rasterization,
+            self.chars.chars(),
+            &mut texture_atlases,
+            &mut textures,
+            &text_settings,
+        ) {
+            warn!("Failed to preload glyphs for font {:?}: {}", self.font, e);
+        }
+    }
+}
+
+/// [`Commands`] extension for preloading glyphs into font atlases
+pub trait PreloadGlyphsExt {
+    /// Rasterizes `chars` of `font` at `font_size` (in physical pixels) into its font atlas for
+    /// `rasterization`.
+    ///
+    /// ```
+    /// # use bevy_asset::Handle;
+    /// # use bevy_ecs::system::Commands;
+    /// # use bevy_text::{Font, PreloadGlyphsExt, RasterizationMode};
+    /// fn preload_score_digits(mut commands: Commands, font: Handle<Font>) {
+    ///     commands.preload_glyphs(font, 40.0, RasterizationMode::Antialiased, "0123456789");
+    /// }
+    /// ```
+    fn preload_glyphs(
+        &mut self,
+        font: Handle<Font>,
+        font_size: f32,
+        rasterization: RasterizationMode,
+        chars: impl Into<String>,
+    ) -> &mut Self;
+}
+
+impl<'a> PreloadGlyphsExt for Commands<'a> {
+    fn preload_glyphs(
+        &mut self,
+        font: Handle<Font>,
+        font_size: f32,
+        rasterization: RasterizationMode,
+        chars: impl Into<String>,
+    ) -> &mut Self {
+        self.add(PreloadGlyphs {
+            font,
+            font_size,
+            rasterization,
+            chars: chars.into(),
+        });
+        self
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::float_cmp)]
+mod tests {
+    use super::FontAtlasSet;
+    use crate::{test_utils::app_with_font, Font, RasterizationMode, TextSettings};
+    use ab_glyph::{Font as _, ScaleFont as _};
+    use bevy_asset::{Assets, Handle};
+    use bevy_render::texture::{FilterMode, Texture};
+    use bevy_sprite::TextureAtlas;
+
+    #[test]
+    fn glyph_info_reports_advance() {
+        let (app, font) = app_with_font();
+        let world = app.world.cell();
+        let fonts = world.get_resource::<Assets<Font>>().unwrap();
+        let mut texture_atlases = world.get_resource_mut::<Assets<TextureAtlas>>().unwrap();
+        let mut textures = world.get_resource_mut::<Assets<Texture>>().unwrap();
+        let font = fonts.get(&font).unwrap();
+        let text_settings = TextSettings::default();
+        let mut font_atlas_set = FontAtlasSet::default();
+        let mut glyph_info = |c| {
+            font_atlas_set
+                .glyph_info(
+                    font,
+                    20.0,
+                    RasterizationMode::Antialiased,
+                    c,
+                    &mut texture_atlases,
+                    &mut textures,
+                    &text_settings,
+                )
+                .unwrap()
+        };
+
+        // every glyph of a monospace font has the same advance
+        let advance = font.font.as_scaled(20.0).h_advance(font.font.glyph_id('W'));
+        assert!((advance - 10.0).abs() < 1e-3);
+
+        let a = glyph_info('a').unwrap();
+        assert_eq!(a.advance, advance);
+        assert!(a.atlas.is_some());
+
+        let space = glyph_info(' ').unwrap();
+        assert_eq!(space.advance, advance);
+        assert!(space.atlas.is_none());
+
+        assert!(glyph_info('\u{1F600}').is_none());
+    }
+
+    #[test]
+    fn rasterization_modes_use_separate_atlases() {
+        let (app, font) = app_with_font();
+        let world = app.world.cell();
+        let fonts = world.get_resource::<Assets<Font>>().unwrap();
+        let mut texture_atlases = world.get_resource_mut::<Assets<TextureAtlas>>().unwrap();
+        let mut textures = world.get_resource_mut::<Assets<Texture>>().unwrap();
+        let font = fonts.get(&font).unwrap();
+        let text_settings = TextSettings::default();
+        let mut font_atlas_set = FontAtlasSet::default();
+        let mut atlas_texture = |rasterization| {
+            font_atlas_set
+                .glyph_info(
+                    font,
+                    20.0,
+                    rasterization,
+                    'a',
+                    &mut texture_atlases,
+                    &mut textures,
+                    &text_settings,
+                )
+                .unwrap()
+                .unwrap()
+                .atlas
+                .unwrap()
+                .texture
+        };
+
+        let antialiased = atlas_texture(RasterizationMode::Antialiased);
+        let pixelated = atlas_texture(RasterizationMode::Pixelated);
+        assert_ne!(antialiased, pixelated);
+        assert_eq!(atlas_texture(RasterizationMode::Antialiased), antialiased);
+
+        let filter = |texture: Handle<Texture>| textures.get(&texture).unwrap().sampler.min_filter;
+        assert_eq!(filter(antialiased), FilterMode::Linear);
+        assert_eq!(filter(pixelated), FilterMode::Nearest);
+    }
+}
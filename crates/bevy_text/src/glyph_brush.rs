@@ -1,16 +1,232 @@
-use ab_glyph::{Font as _, FontArc, Glyph, ScaleFont as _};
+use ab_glyph::{
+    v2, CodepointIdIter, Font as _, FontArc, Glyph, GlyphId, GlyphSvg, Outline, ScaleFont as _,
+};
 use bevy_asset::{Assets, Handle};
 use bevy_math::{Size, Vec2};
 use bevy_render::prelude::Texture;
 use bevy_sprite::TextureAtlas;
 use glyph_brush_layout::{
-    FontId, GlyphPositioner, Layout, SectionGeometry, SectionGlyph, SectionText, ToSectionText,
+    BuiltInLineBreaker, FontId, GlyphPositioner, Layout, LineBreak, LineBreaker, SectionGeometry,
+    SectionGlyph, SectionText, ToSectionText,
+};
+use std::ops::Range;
+
+use crate::{
+    error::TextError, Font, FontAtlasSet, GlyphAtlasInfo, GlyphPositioning, MissingGlyphPolicy,
+    RasterizationMode, TextAlignment, TextSettings,
 };
 
-use crate::{error::TextError, Font, FontAtlasSet, GlyphAtlasInfo, TextAlignment};
+/// The glyph fonts map characters they don't support to
+pub(crate) const NOTDEF: GlyphId = GlyphId(0);
+
+/// The glyph [`LayoutFont`] maps zero width characters to. It has no advance and no outline.
+pub(crate) const ZERO_WIDTH: GlyphId = GlyphId(u16::MAX);
+
+/// Line breaking that follows the Unicode line breaking algorithm, except that runs of text joined
+/// by a no-break space (U+00A0, U+2007 or U+202F) or a word joiner (U+2060) are never broken
+/// apart. A run ends at breaking whitespace or a zero width space (U+200B), so "100\u{A0}km/h"
+/// always stays on one line even though the algorithm allows a break after the slash. A run that
+/// is wider than the bounds overflows instead of wrapping.
+#[derive(Debug, Default, Clone, Copy, Hash)]
+struct NoBreakLineBreaker;
+
+impl LineBreaker for NoBreakLineBreaker {
+    fn line_breaks<'a>(&self, text: &'a str) -> Box<dyn Iterator<Item = LineBreak> + 'a> {
+        let line_breaks = BuiltInLineBreaker::UnicodeLineBreaker.line_breaks(text);
+        let runs = no_break_runs(text);
+        if runs.is_empty() {
+            return line_breaks;
+        }
+        Box::new(line_breaks.filter(move |line_break| {
+            match *line_break {
+                LineBreak::Soft(offset) => !runs
+                    .iter()
+                    .any(|run| run.start < offset && offset < run.end),
+                LineBreak::Hard(_) => true,
+            }
+        }))
+    }
+}
+
+fn is_no_break(c: char) -> bool {
+    matches!(c, '\u{A0}' | '\u{2007}' | '\u{202F}' | '\u{2060}')
+}
+
+/// Characters that only affect line breaking and are never drawn
+pub(crate) fn is_zero_width(c: char) -> bool {
+    matches!(c, '\u{200B}' | '\u{2060}' | '\u{FEFF}')
+}
+
+/// Characters that control line breaking, which must reach the line breaker unchanged
+pub(crate) fn is_line_break_control(c: char) -> bool {
+    is_no_break(c) || is_zero_width(c)
+}
+
+/// Byte ranges of the runs in `text` that contain a no-break character
+fn no_break_runs(text: &str) -> Vec<Range<usize>> {
+    let mut runs = Vec::new();
+    let mut start = 0;
+    let mut no_break = false;
+    for (byte_index, c) in text.char_indices() {
+        if (c.is_whitespace() && !is_no_break(c)) || c == '\u{200B}' {
+            if no_break {
+                runs.push(start..byte_index);
+            }
+            start = byte_index + c.len_utf8();
+            no_break = false;
+        } else if is_no_break(c) {
+            no_break = true;
+        }
+    }
+    if no_break {
+        runs.push(start..text.len());
+    }
+    runs
+}
+
+/// Turns the `(section_index, byte_index)` of a glyph into the index of its character, counting
+/// characters across all sections
+pub(crate) struct CharIndexer {
+    /// Byte offset of every character, per section
+    char_offsets: Vec<Vec<usize>>,
+    /// Number of characters before each section
+    section_starts: Vec<usize>,
+}
+
+impl CharIndexer {
+    pub fn new(sections: &[SectionText]) -> Self {
+        let char_offsets = sections
+            .iter()
+            .map(|section| {
+                section
+                    .text
+                    .char_indices()
+                    .map(|(byte_index, _)| byte_index)
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let section_starts = char_offsets
+            .iter()
+            .scan(0, |start, offsets| {
+                let section_start = *start;
+                *start += offsets.len();
+                Some(section_start)
+            })
+            .collect();
+        Self {
+            char_offsets,
+            section_starts,
+        }
+    }
+
+    pub fn char_index(&self, section_index: usize, byte_index: usize) -> usize {
+        let char_index = self.char_offsets[section_index]
+            .binary_search(&byte_index)
+            .unwrap_or_else(|index| index);
+        self.section_starts[section_index] + char_index
+    }
+}
+
+/// A font as seen by layout. Zero width characters map to [`ZERO_WIDTH`] so they take up no
+/// space even though most fonts have no glyph for them, and no-break spaces the font is missing
+/// use its regular space instead.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct LayoutFont<F>(pub F);
+
+impl<F: ab_glyph::Font> ab_glyph::Font for LayoutFont<F> {
+    fn units_per_em(&self) -> Option<f32> {
+        self.0.units_per_em()
+    }
+
+    fn ascent_unscaled(&self) -> f32 {
+        self.0.ascent_unscaled()
+    }
+
+    fn descent_unscaled(&self) -> f32 {
+        self.0.descent_unscaled()
+    }
+
+    fn line_gap_unscaled(&self) -> f32 {
+        self.0.line_gap_unscaled()
+    }
+
+    fn glyph_id(&self, c: char) -> GlyphId {
+        if is_zero_width(c) {
+            return ZERO_WIDTH;
+        }
+        match self.0.glyph_id(c) {
+            NOTDEF if is_no_break(c) && c.is_whitespace() => self.0.glyph_id(' '),
+            glyph_id => glyph_id,
+        }
+    }
+
+    fn h_advance_unscaled(&self, id: GlyphId) -> f32 {
+        if id == ZERO_WIDTH {
+            return 0.;
+        }
+        self.0.h_advance_unscaled(id)
+    }
+
+    fn h_side_bearing_unscaled(&self, id: GlyphId) -> f32 {
+        if id == ZERO_WIDTH {
+            return 0.;
+        }
+        self.0.h_side_bearing_unscaled(id)
+    }
+
+    fn v_advance_unscaled(&self, id: GlyphId) -> f32 {
+        if id == ZERO_WIDTH {
+            return 0.;
+        }
+        self.0.v_advance_unscaled(id)
+    }
+
+    fn v_side_bearing_unscaled(&self, id: GlyphId) -> f32 {
+        if id == ZERO_WIDTH {
+            return 0.;
+        }
+        self.0.v_side_bearing_unscaled(id)
+    }
+
+    fn kern_unscaled(&self, first: GlyphId, second: GlyphId) -> f32 {
+        if first == ZERO_WIDTH || second == ZERO_WIDTH {
+            return 0.;
+        }
+        self.0.kern_unscaled(first, second)
+    }
+
+    fn outline(&self, id: GlyphId) -> Option<Outline> {
+        if id == ZERO_WIDTH {
+            return None;
+        }
+        self.0.outline(id)
+    }
+
+    fn glyph_count(&self) -> usize {
+        self.0.glyph_count()
+    }
+
+    fn codepoint_ids(&self) -> CodepointIdIter<'_> {
+        self.0.codepoint_ids()
+    }
+
+    fn glyph_raster_image2(&self, id: GlyphId, pixel_size: u16) -> Option<v2::GlyphImage<'_>> {
+        if id == ZERO_WIDTH {
+            return None;
+        }
+        self.0.glyph_raster_image2(id, pixel_size)
+    }
+
+    fn glyph_svg_image(&self, id: GlyphId) -> Option<GlyphSvg<'_>> {
+        if id == ZERO_WIDTH {
+            return None;
+        }
+        self.0.glyph_svg_image(id)
+    }
+}
 
 pub struct GlyphBrush {
-    fonts: Vec<FontArc>,
+    fonts: Vec<LayoutFont<FontArc>>,
     handles: Vec<Handle<Font>>,
     latest_font_id: FontId,
 }
@@ -36,21 +252,28 @@ impl GlyphBrush {
             bounds: (bounds.width, bounds.height),
             ..Default::default()
         };
-        let section_glyphs = Layout::default()
-            .h_align(text_alignment.horizontal)
-            .v_align(text_alignment.vertical)
-            .calculate_glyphs(&self.fonts, &geom, sections);
+        let section_glyphs = Layout::Wrap {
+            line_breaker: NoBreakLineBreaker,
+            h_align: text_alignment.horizontal,
+            v_align: text_alignment.vertical,
+        }
+        .calculate_glyphs(&self.fonts, &geom, sections);
         Ok(section_glyphs)
     }
 
+    /// Rasterizes and positions `glyphs`. `rasterizations` holds the rasterization mode of each
+    /// section.
+    #[allow(clippy::too_many_arguments)]
     pub fn process_glyphs(
         &self,
         glyphs: Vec<SectionGlyph>,
         sections: &[SectionText],
+        rasterizations: &[RasterizationMode],
         font_atlas_set_storage: &mut Assets<FontAtlasSet>,
         fonts: &Assets<Font>,
         texture_atlases: &mut Assets<TextureAtlas>,
         textures: &mut Assets<Texture>,
+        text_settings: &TextSettings,
     ) -> Result<Vec<PositionedGlyph>, TextError> {
         if glyphs.is_empty() {
             return Ok(Vec::new());
@@ -82,6 +305,7 @@ impl GlyphBrush {
         max_y = max_y.floor();
         min_x = min_x.floor();
 
+        let char_indexer = CharIndexer::new(sections);
         let mut positioned_glyphs = Vec::new();
         for sg in glyphs {
             let SectionGlyph {
@@ -91,9 +315,17 @@ impl GlyphBrush {
                 font_id: _,
             } = sg;
             let glyph_id = glyph.id;
+            if glyph_id == NOTDEF && text_settings.missing_glyph == MissingGlyphPolicy::Hidden {
+                continue;
+            }
+            if glyph_id == ZERO_WIDTH {
+                continue;
+            }
+            let adjust = GlyphPlacementAdjuster::new(&mut glyph, text_settings.glyph_positioning);
+            // the atlas is keyed on the position the glyph is actually rasterized at
             let glyph_position = glyph.position;
-            let adjust = GlyphPlacementAdjuster::new(&mut glyph);
             let section_data = sections_data[sg.section_index];
+            let rasterization = rasterizations[sg.section_index];
             if let Some(outlined_glyph) = section_data.1.font.outline_glyph(glyph) {
                 let bounds = outlined_glyph.px_bounds();
                 let handle_font_atlas: Handle<FontAtlasSet> = section_data.0.as_weak();
@@ -101,10 +333,16 @@ impl GlyphBrush {
                     .get_or_insert_with(handle_font_atlas, FontAtlasSet::default);
 
                 let atlas_info = font_atlas_set
-                    .get_glyph_atlas_info(section_data.2, glyph_id, glyph_position)
+                    .get_glyph_atlas_info(section_data.2, glyph_id, glyph_position, rasterization)
                     .map(Ok)
                     .unwrap_or_else(|| {
-                        font_atlas_set.add_glyph_to_atlas(texture_atlases, textures, outlined_glyph)
+                        font_atlas_set.add_glyph_to_atlas(
+                            texture_atlases,
+                            textures,
+                            outlined_glyph,
+                            rasterization,
+                            text_settings,
+                        )
                     })?;
 
                 let texture_atlas = texture_atlases.get(&atlas_info.texture_atlas).unwrap();
@@ -121,6 +359,7 @@ impl GlyphBrush {
                     atlas_info,
                     section_index: sg.section_index,
                     byte_index,
+                    char_index: char_indexer.char_index(sg.section_index, byte_index),
                 });
             }
         }
@@ -128,7 +367,7 @@ impl GlyphBrush {
     }
 
     pub fn add_font(&mut self, handle: Handle<Font>, font: FontArc) -> FontId {
-        self.fonts.push(font);
+        self.fonts.push(LayoutFont(font));
         self.handles.push(handle);
         let font_id = self.latest_font_id;
         self.latest_font_id = FontId(font_id.0 + 1);
@@ -142,40 +381,76 @@ pub struct PositionedGlyph {
     pub size: Vec2,
     pub atlas_info: GlyphAtlasInfo,
     pub section_index: usize,
+    /// Byte offset of the glyph's character in the laid out text of its section. That text
+    /// differs from [`TextSection::value`](crate::TextSection) when
+    /// [`MissingGlyphPolicy::Replace`] swapped characters, so prefer
+    /// [`PositionedGlyph::char_index`].
     pub byte_index: usize,
+    /// Index of the glyph's character, counting characters across all sections. Replacing missing
+    /// glyphs swaps whole characters, so this also indexes the original text.
+    pub char_index: usize,
 }
 
-#[cfg(feature = "subpixel_glyph_atlas")]
-struct GlyphPlacementAdjuster;
+struct GlyphPlacementAdjuster(f32);
 
-#[cfg(feature = "subpixel_glyph_atlas")]
 impl GlyphPlacementAdjuster {
     #[inline(always)]
-    pub fn new(_: &mut Glyph) -> Self {
-        Self
+    pub fn new(glyph: &mut Glyph, positioning: GlyphPositioning) -> Self {
+        match positioning {
+            GlyphPositioning::PixelSnapped => {
+                let v = glyph.position.x.round();
+                glyph.position.x = 0.;
+                glyph.position.y = glyph.position.y.ceil();
+                Self(v)
+            }
+            // the glyph is rasterized at its fractional offset, so its bounds already carry it
+            #[cfg(feature = "subpixel_glyph_atlas")]
+            GlyphPositioning::Subpixel => Self(0.),
+            // the atlas stores a single rasterization per glyph, so only the quad is offset
+            #[cfg(not(feature = "subpixel_glyph_atlas"))]
+            GlyphPositioning::Subpixel => {
+                let v = glyph.position.x;
+                glyph.position.x = 0.;
+                glyph.position.y = glyph.position.y.ceil();
+                Self(v)
+            }
+        }
     }
 
     #[inline(always)]
-    pub fn position(&self, p: Vec2) -> Vec2 {
-        p
+    pub fn position(&self, v: Vec2) -> Vec2 {
+        Vec2::new(self.0, 0.) + v
     }
 }
 
-#[cfg(not(feature = "subpixel_glyph_atlas"))]
-struct GlyphPlacementAdjuster(f32);
+#[cfg(test)]
+mod tests {
+    use super::NoBreakLineBreaker;
+    use glyph_brush_layout::{LineBreak, LineBreaker};
 
-#[cfg(not(feature = "subpixel_glyph_atlas"))]
-impl GlyphPlacementAdjuster {
-    #[inline(always)]
-    pub fn new(glyph: &mut Glyph) -> Self {
-        let v = glyph.position.x.round();
-        glyph.position.x = 0.;
-        glyph.position.y = glyph.position.y.ceil();
-        Self(v)
+    fn soft_breaks(text: &str) -> Vec<usize> {
+        NoBreakLineBreaker
+            .line_breaks(text)
+            .filter_map(|line_break| match line_break {
+                // the end of the text is always a break opportunity
+                LineBreak::Soft(offset) if offset < text.len() => Some(offset),
+                _ => None,
+            })
+            .collect()
     }
 
-    #[inline(always)]
-    pub fn position(&self, v: Vec2) -> Vec2 {
-        Vec2::new(self.0, 0.) + v
+    #[test]
+    fn no_break_runs_stay_together() {
+        // the break the unicode algorithm allows after the slash is dropped
+        assert_eq!(soft_breaks("100\u{A0}km/h and more"), vec![10, 14]);
+        assert_eq!(soft_breaks("km\u{2060}/h"), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn breaks_outside_no_break_runs_are_kept() {
+        assert_eq!(soft_breaks("a b"), vec![2]);
+        assert_eq!(soft_breaks("a\u{A0}b c"), vec![5]);
+        // a zero width space ends a run and allows a break
+        assert_eq!(soft_breaks("a\u{A0}b\u{200B}c"), vec![7]);
     }
 }
@@ -28,10 +28,83 @@ pub mod prelude {
 use bevy_app::prelude::*;
 use bevy_asset::AddAsset;
 use bevy_ecs::{entity::Entity, system::IntoSystem};
+use bevy_math::Vec2;
 use bevy_render::RenderStage;
 
 pub type DefaultTextPipeline = TextPipeline<Entity>;
 
+/// Settings used when laying out text and rasterizing glyphs into font atlases
+///
+/// Insert this resource before adding the [`TextPlugin`] to override the defaults. Changing it
+/// later lays out all text again.
+#[derive(Debug, Clone)]
+pub struct TextSettings {
+    /// Size in pixels of each font atlas texture. Larger atlases mean fewer atlas pages for big
+    /// fonts at the cost of more texture memory per page.
+    pub font_atlas_size: Vec2,
+    /// Number of atlas pages a single [`FontAtlasSet`] may use for one font size before a warning
+    /// is logged. Pages beyond this limit are still allocated, so glyphs are never dropped.
+    pub soft_max_font_atlases: usize,
+    /// How glyph quads are placed relative to the pixel grid.
+    pub glyph_positioning: GlyphPositioning,
+    /// What to draw for characters the section's font has no glyph for.
+    pub missing_glyph: MissingGlyphPolicy,
+}
+
+impl Default for TextSettings {
+    fn default() -> Self {
+        Self {
+            font_atlas_size: Vec2::new(512.0, 512.0),
+            soft_max_font_atlases: 16,
+            glyph_positioning: Default::default(),
+            missing_glyph: Default::default(),
+        }
+    }
+}
+
+/// How glyph quads are placed relative to the pixel grid
+///
+/// Measurement is unaffected by this setting, so switching modes never changes the size of laid
+/// out text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GlyphPositioning {
+    /// Glyphs are snapped to whole pixels horizontally. Crisp, which suits pixel-art fonts, but
+    /// the spacing between glyphs can look uneven at small sizes.
+    PixelSnapped,
+    /// Glyphs keep the fractional horizontal position computed by layout. Spacing stays even, but
+    /// glyphs that don't land on a pixel boundary are filtered and look softer. With the
+    /// `subpixel_glyph_atlas` feature each fractional offset is rasterized separately instead.
+    Subpixel,
+}
+
+impl Default for GlyphPositioning {
+    fn default() -> Self {
+        if cfg!(feature = "subpixel_glyph_atlas") {
+            GlyphPositioning::Subpixel
+        } else {
+            GlyphPositioning::PixelSnapped
+        }
+    }
+}
+
+/// What to draw for a character the font has no glyph for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MissingGlyphPolicy {
+    /// Leave a gap the width of the font's `.notdef` glyph, drawing nothing.
+    Hidden,
+    /// Draw the font's `.notdef` glyph, usually an empty box.
+    Notdef,
+    /// Lay out and draw this character instead. If the font is missing it as well, its `.notdef`
+    /// glyph is drawn.
+    Replace(char),
+}
+
+impl Default for MissingGlyphPolicy {
+    fn default() -> Self {
+        MissingGlyphPolicy::Notdef
+    }
+}
+
 #[derive(Default)]
 pub struct TextPlugin;
 
@@ -40,8 +113,41 @@ impl Plugin for TextPlugin {
         app.add_asset::<Font>()
             .add_asset::<FontAtlasSet>()
             .init_asset_loader::<FontLoader>()
+            .init_resource::<TextSettings>()
             .insert_resource(DefaultTextPipeline::default())
             .add_system_to_stage(CoreStage::PostUpdate, text2d_system.system())
             .add_system_to_stage(RenderStage::Draw, text2d::draw_text2d_system.system());
     }
 }
+
+#[cfg(test)]
+mod test_utils {
+    use crate::{Font, FontAtlasSet};
+    use bevy_app::App;
+    use bevy_asset::{AddAsset, AssetPlugin, Assets, Handle};
+    use bevy_core::CorePlugin;
+    use bevy_render::texture::Texture;
+    use bevy_sprite::TextureAtlas;
+
+    /// Builds an app with the assets that text layout uses and adds FiraMono, a monospace font,
+    /// to it. The app has to outlive the returned handle.
+    pub(crate) fn app_with_font() -> (App, Handle<Font>) {
+        let mut app = App::build();
+        app.add_plugin(CorePlugin)
+            .add_plugin(AssetPlugin)
+            .add_asset::<Texture>()
+            .add_asset::<TextureAtlas>()
+            .add_asset::<Font>()
+            .add_asset::<FontAtlasSet>();
+        let font = Font::try_from_bytes(
+            include_bytes!("../../../assets/fonts/FiraMono-Medium.ttf").to_vec(),
+        )
+        .unwrap();
+        let font = app
+            .world_mut()
+            .get_resource_mut::<Assets<Font>>()
+            .unwrap()
+            .add(font);
+        (app.app, font)
+    }
+}
@@ -1,19 +1,24 @@
 use std::hash::Hash;
 
-use ab_glyph::{PxScale, ScaleFont};
+use ab_glyph::{Font as _, FontArc, Glyph, PxScale, PxScaleFont, ScaleFont};
 use bevy_asset::{Assets, Handle, HandleId};
-use bevy_math::Size;
+use bevy_math::{Size, Vec2};
 use bevy_render::prelude::Texture;
-use bevy_sprite::TextureAtlas;
+use bevy_sprite::{Rect, TextureAtlas};
 use bevy_utils::HashMap;
 
-use glyph_brush_layout::{FontId, SectionText};
+use glyph_brush_layout::{FontId, SectionGlyph, SectionText};
 
 use crate::{
-    error::TextError, glyph_brush::GlyphBrush, scale_value, Font, FontAtlasSet, PositionedGlyph,
-    TextAlignment, TextSection,
+    error::TextError,
+    glyph_brush::{is_line_break_control, CharIndexer, GlyphBrush, LayoutFont, NOTDEF},
+    scale_value, Font, FontAtlasSet, MissingGlyphPolicy, PositionedGlyph, TextAlignment,
+    TextAutoFit, TextSection, TextSettings,
 };
 
+/// How close, in logical pixels, [`TextAutoFit`] gets to the largest font size that fits
+const AUTO_FIT_PRECISION: f32 = 0.5;
+
 pub struct TextPipeline<ID> {
     brush: GlyphBrush,
     glyph_map: HashMap<ID, TextLayoutInfo>,
@@ -33,6 +38,80 @@ impl<ID> Default for TextPipeline<ID> {
 pub struct TextLayoutInfo {
     pub glyphs: Vec<PositionedGlyph>,
     pub size: Size,
+    /// Whether glyphs were dropped because of [`Text::max_lines`](crate::Text::max_lines) or
+    /// because they overflowed the bounds of [`Text::auto_fit`](crate::Text::auto_fit) text
+    pub truncated: bool,
+    /// The area covered by each line that contains glyphs, from top to bottom, in the same space
+    /// as the [`PositionedGlyph`] positions: relative to the bottom left of the text, y up
+    pub line_rects: Vec<Rect>,
+    /// The font size [`Text::auto_fit`](crate::Text::auto_fit) chose for the largest section, in
+    /// logical pixels. Every section's font size was scaled by the same factor, so a section
+    /// ended up at `fitted_font_size / largest_font_size` times its own font size.
+    pub fitted_font_size: Option<f32>,
+    caret_stops: Vec<CaretStop>,
+}
+
+/// Where a caret can be placed around one laid out character
+#[derive(Debug, Clone, Copy)]
+struct CaretStop {
+    /// Character index across all sections
+    index: usize,
+    /// Index into [`TextLayoutInfo::line_rects`]
+    line: usize,
+    left: f32,
+    right: f32,
+}
+
+impl TextLayoutInfo {
+    /// Returns the position of a caret placed before the character at `index`, counting
+    /// characters across all sections. An index past the last character places the caret after
+    /// it. The position is the bottom of the caret, which spans the height of its line in
+    /// [`TextLayoutInfo::line_rects`], in the same space as the glyph positions.
+    ///
+    /// Characters that produce no glyph, such as line breaks, put the caret after the previous
+    /// character, and lines without any glyphs can't hold the caret. Returns `None` if the text
+    /// has no glyphs.
+    pub fn caret_position(&self, index: usize) -> Option<Vec2> {
+        let (x, line) = match self
+            .caret_stops
+            .binary_search_by_key(&index, |caret| caret.index)
+        {
+            Ok(i) => (self.caret_stops[i].left, self.caret_stops[i].line),
+            Err(0) => {
+                let first = self.caret_stops.first()?;
+                (first.left, first.line)
+            }
+            Err(i) => (self.caret_stops[i - 1].right, self.caret_stops[i - 1].line),
+        };
+        Some(Vec2::new(x, self.line_rects[line].min.y))
+    }
+
+    /// Returns the character index whose caret position is closest to `position`, given in the
+    /// same space as the glyph positions. This is the inverse of
+    /// [`TextLayoutInfo::caret_position`], for placing the caret where the text was clicked.
+    pub fn index_at_position(&self, position: Vec2) -> usize {
+        let line = match self
+            .line_rects
+            .iter()
+            .position(|rect| position.y >= rect.min.y)
+        {
+            Some(line) => line,
+            // below the last line
+            None => match self.line_rects.len().checked_sub(1) {
+                Some(line) => line,
+                None => return 0,
+            },
+        };
+
+        let mut index = None;
+        for caret in self.caret_stops.iter().filter(|caret| caret.line == line) {
+            if position.x < (caret.left + caret.right) / 2. {
+                return caret.index;
+            }
+            index = Some(caret.index + 1);
+        }
+        index.unwrap_or(0)
+    }
 }
 
 impl<ID: Hash + Eq> TextPipeline<ID> {
@@ -56,12 +135,118 @@ impl<ID: Hash + Eq> TextPipeline<ID> {
         sections: &[TextSection],
         scale_factor: f64,
         text_alignment: TextAlignment,
+        max_lines: Option<usize>,
+        auto_fit: Option<TextAutoFit>,
         bounds: Size,
         font_atlas_set_storage: &mut Assets<FontAtlasSet>,
         texture_atlases: &mut Assets<TextureAtlas>,
         textures: &mut Assets<Texture>,
+        text_settings: &TextSettings,
     ) -> Result<(), TextError> {
-        let mut scaled_fonts = Vec::new();
+        let replaced_sections = match text_settings.missing_glyph {
+            MissingGlyphPolicy::Replace(replacement) => {
+                replace_missing_glyphs(fonts, sections, replacement)?
+            }
+            _ => None,
+        };
+        let sections = replaced_sections.as_deref().unwrap_or(sections);
+
+        let largest_font_size = largest_font_size(sections);
+        let fitted_font_size = match auto_fit {
+            Some(auto_fit) if largest_font_size > 0. => Some(self.fit_font_size(
+                fonts,
+                sections,
+                scale_factor,
+                text_alignment,
+                max_lines,
+                auto_fit,
+                bounds,
+            )?),
+            _ => None,
+        };
+        let font_scale = fitted_font_size.map_or(1., |font_size| font_size / largest_font_size);
+        let rasterizations = sections
+            .iter()
+            .map(|section| section.style.rasterization)
+            .collect::<Vec<_>>();
+
+        let (sections, section_fonts) =
+            self.section_texts(fonts, sections, scale_factor, font_scale)?;
+
+        let mut section_glyphs = self
+            .brush
+            .compute_glyphs(&sections, bounds, text_alignment)?;
+
+        let mut truncated = max_lines.map_or(false, |max_lines| {
+            truncate_lines(&mut section_glyphs, &sections, max_lines)
+        });
+        grow_shifted_lines(&mut section_glyphs, &section_fonts);
+
+        if auto_fit.map_or(false, |auto_fit| auto_fit.clip_overflow) {
+            truncated |= clip_to_bounds(&mut section_glyphs, &section_fonts, bounds);
+        }
+
+        let size = match measure(&section_glyphs, &section_fonts) {
+            Some(size) => size,
+            None => {
+                self.glyph_map.insert(
+                    id,
+                    TextLayoutInfo {
+                        glyphs: Vec::new(),
+                        size: Size::new(0., 0.),
+                        line_rects: Vec::new(),
+                        caret_stops: Vec::new(),
+                        truncated,
+                        fitted_font_size,
+                    },
+                );
+                return Ok(());
+            }
+        };
+
+        let (line_rects, caret_stops) =
+            lines_and_carets(&section_glyphs, &sections, &section_fonts);
+
+        for sg in section_glyphs.iter_mut() {
+            sg.glyph.position.y += section_fonts[sg.section_index].baseline_offset;
+        }
+
+        let glyphs = self.brush.process_glyphs(
+            section_glyphs,
+            &sections,
+            &rasterizations,
+            font_atlas_set_storage,
+            fonts,
+            texture_atlases,
+            textures,
+            text_settings,
+        )?;
+
+        self.glyph_map.insert(
+            id,
+            TextLayoutInfo {
+                glyphs,
+                size,
+                line_rects,
+                caret_stops,
+                truncated,
+                fitted_font_size,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Builds the glyph_brush sections for `sections`, multiplying every section's font size by
+    /// `font_scale`.
+    fn section_texts<'a>(
+        &mut self,
+        fonts: &'a Assets<Font>,
+        sections: &'a [TextSection],
+        scale_factor: f64,
+        font_scale: f32,
+    ) -> Result<(Vec<SectionText<'a>>, Vec<SectionFont<'a>>), TextError> {
+        let mut section_fonts = Vec::new();
         let sections = sections
             .iter()
             .map(|section| {
@@ -69,9 +254,12 @@ impl<ID: Hash + Eq> TextPipeline<ID> {
                     .get(section.style.font.id)
                     .ok_or(TextError::NoSuchFont)?;
                 let font_id = self.get_or_insert_font_id(&section.style.font, font);
-                let font_size = scale_value(section.style.font_size, scale_factor);
+                let font_size = scale_value(section.style.font_size * font_scale, scale_factor);
 
-                scaled_fonts.push(ab_glyph::Font::as_scaled(&font.font, font_size));
+                section_fonts.push(SectionFont {
+                    font: LayoutFont(&font.font).into_scaled(font_size),
+                    baseline_offset: -section.style.baseline_shift * font_size,
+                });
 
                 let section = SectionText {
                     font_id,
@@ -83,48 +271,762 @@ impl<ID: Hash + Eq> TextPipeline<ID> {
             })
             .collect::<Result<Vec<_>, _>>()?;
 
-        let section_glyphs = self
-            .brush
-            .compute_glyphs(&sections, bounds, text_alignment)?;
+        Ok((sections, section_fonts))
+    }
+
+    /// Binary searches the largest font size within `auto_fit`'s range for the largest section
+    /// whose layout fits in `bounds`, falling back to `min_size` when nothing fits. The other
+    /// sections are scaled by the same factor.
+    #[allow(clippy::too_many_arguments)]
+    fn fit_font_size(
+        &mut self,
+        fonts: &Assets<Font>,
+        sections: &[TextSection],
+        scale_factor: f64,
+        text_alignment: TextAlignment,
+        max_lines: Option<usize>,
+        auto_fit: TextAutoFit,
+        bounds: Size,
+    ) -> Result<f32, TextError> {
+        let largest_font_size = largest_font_size(sections);
+        // layout drops the lines that start below the bounds, so lay out without a height limit to
+        // see the lines that don't fit
+        let unbounded_height = Size::new(bounds.width, f32::MAX);
+        let mut fits = |font_size: f32| -> Result<bool, TextError> {
+            let (sections, section_fonts) =
+                self.section_texts(fonts, sections, scale_factor, font_size / largest_font_size)?;
+            let mut section_glyphs =
+                self.brush
+                    .compute_glyphs(&sections, unbounded_height, text_alignment)?;
+            if let Some(max_lines) = max_lines {
+                truncate_lines(&mut section_glyphs, &sections, max_lines);
+            }
+            grow_shifted_lines(&mut section_glyphs, &section_fonts);
+            Ok(
+                measure(&section_glyphs, &section_fonts).map_or(true, |size| {
+                    size.width <= bounds.width && size.height <= bounds.height
+                }),
+            )
+        };
+
+        let (mut low, mut high) = (auto_fit.min_size, auto_fit.max_size.max(auto_fit.min_size));
+        if fits(high)? {
+            return Ok(high);
+        }
+        while high - low > AUTO_FIT_PRECISION {
+            let mid = (low + high) / 2.;
+            if fits(mid)? {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+
+        Ok(low)
+    }
+}
+
+/// The scaled font of a section, and how far its glyphs are moved from their line's baseline
+#[derive(Clone, Copy)]
+struct SectionFont<'a> {
+    font: PxScaleFont<LayoutFont<&'a FontArc>>,
+    /// Vertical offset in layout space, where positive values move glyphs down
+    baseline_offset: f32,
+}
+
+impl SectionFont<'_> {
+    fn top(&self, glyph: &Glyph) -> f32 {
+        glyph.position.y + self.baseline_offset - self.font.ascent()
+    }
+
+    fn bottom(&self, glyph: &Glyph) -> f32 {
+        glyph.position.y + self.baseline_offset - self.font.descent()
+    }
+
+    fn h_advance(&self, glyph: &Glyph) -> f32 {
+        self.font.h_advance(glyph.id)
+    }
+}
+
+fn largest_font_size(sections: &[TextSection]) -> f32 {
+    sections
+        .iter()
+        .map(|section| section.style.font_size)
+        .fold(0., f32::max)
+}
+
+/// Copies `sections` with every character their font has no glyph for replaced by
+/// `replacement`, or returns `None` if no character is missing. Characters that control line
+/// breaking are kept even if the font lacks them, since layout handles them itself.
+fn replace_missing_glyphs(
+    fonts: &Assets<Font>,
+    sections: &[TextSection],
+    replacement: char,
+) -> Result<Option<Vec<TextSection>>, TextError> {
+    let mut replaced = None;
+    for (i, section) in sections.iter().enumerate() {
+        let font = fonts
+            .get(section.style.font.id)
+            .ok_or(TextError::NoSuchFont)?;
+        let is_missing = |c: char| {
+            !c.is_control() && !is_line_break_control(c) && font.font.glyph_id(c) == NOTDEF
+        };
+        if !section.value.chars().any(is_missing) {
+            continue;
+        }
+
+        let replaced = replaced.get_or_insert_with(|| sections.to_vec());
+        replaced[i].value = section
+            .value
+            .chars()
+            .map(|c| if is_missing(c) { replacement } else { c })
+            .collect();
+    }
+    Ok(replaced)
+}
+
+/// Moves every line down by how far the shifted glyphs of the lines above it, and its own
+/// shifted glyphs, reach past their unshifted extent, so that a large baseline shift grows the
+/// line instead of overlapping its neighbours.
+#[allow(clippy::float_cmp)]
+fn grow_shifted_lines(section_glyphs: &mut [SectionGlyph], section_fonts: &[SectionFont]) {
+    let mut offset = 0.;
+    let mut start = 0;
+    while start < section_glyphs.len() {
+        let baseline = section_glyphs[start].glyph.position.y;
+        let end = section_glyphs[start..]
+            .iter()
+            .position(|sg| sg.glyph.position.y != baseline)
+            .map_or(section_glyphs.len(), |len| start + len);
+        let line = &mut section_glyphs[start..end];
+
+        let (mut top, mut shifted_top) = (std::f32::MAX, std::f32::MAX);
+        let (mut bottom, mut shifted_bottom) = (std::f32::MIN, std::f32::MIN);
+        for sg in line.iter() {
+            let section_font = section_fonts[sg.section_index];
+            top = top.min(baseline - section_font.font.ascent());
+            shifted_top = shifted_top.min(section_font.top(&sg.glyph));
+            bottom = bottom.max(baseline - section_font.font.descent());
+            shifted_bottom = shifted_bottom.max(section_font.bottom(&sg.glyph));
+        }
+
+        offset += (top - shifted_top).max(0.);
+        for sg in line.iter_mut() {
+            sg.glyph.position.y += offset;
+        }
+        offset += (shifted_bottom - bottom).max(0.);
+        start = end;
+    }
+}
+
+/// Computes the size of the area covered by `section_glyphs`, or `None` if there are none.
+fn measure(section_glyphs: &[SectionGlyph], section_fonts: &[SectionFont]) -> Option<Size> {
+    if section_glyphs.is_empty() {
+        return None;
+    }
 
-        if section_glyphs.is_empty() {
-            self.glyph_map.insert(
-                id,
-                TextLayoutInfo {
-                    glyphs: Vec::new(),
-                    size: Size::new(0., 0.),
+    let mut min_x: f32 = std::f32::MAX;
+    let mut min_y: f32 = std::f32::MAX;
+    let mut max_x: f32 = std::f32::MIN;
+    let mut max_y: f32 = std::f32::MIN;
+
+    for sg in section_glyphs.iter() {
+        let section_font = section_fonts[sg.section_index];
+        let glyph = &sg.glyph;
+        min_x = min_x.min(glyph.position.x);
+        min_y = min_y.min(section_font.top(glyph));
+        max_x = max_x.max(glyph.position.x + section_font.h_advance(glyph));
+        max_y = max_y.max(section_font.bottom(glyph));
+    }
+
+    Some(Size::new(max_x - min_x, max_y - min_y))
+}
+
+/// Computes the area covered by each line of `section_glyphs` and the caret stops of every
+/// glyph, using the same origin as [`GlyphBrush::process_glyphs`] so they line up with the
+/// positioned glyphs.
+#[allow(clippy::float_cmp)]
+fn lines_and_carets(
+    section_glyphs: &[SectionGlyph],
+    sections: &[SectionText],
+    section_fonts: &[SectionFont],
+) -> (Vec<Rect>, Vec<CaretStop>) {
+    let mut min_x = std::f32::MAX;
+    let mut max_y = std::f32::MIN;
+    for sg in section_glyphs.iter() {
+        let section_font = section_fonts[sg.section_index];
+        min_x = min_x.min(sg.glyph.position.x);
+        max_y = max_y.max(section_font.bottom(&sg.glyph));
+    }
+    let min_x = min_x.floor();
+    let max_y = max_y.floor();
+
+    let char_indexer = CharIndexer::new(sections);
+    let mut rects: Vec<Rect> = Vec::new();
+    let mut carets = Vec::with_capacity(section_glyphs.len());
+    let mut baseline = None;
+    for sg in section_glyphs.iter() {
+        let section_font = section_fonts[sg.section_index];
+        let glyph = &sg.glyph;
+        let min = Vec2::new(glyph.position.x - min_x, max_y - section_font.bottom(glyph));
+        let max = Vec2::new(
+            glyph.position.x + section_font.h_advance(glyph) - min_x,
+            max_y - section_font.top(glyph),
+        );
+        match rects.last_mut() {
+            Some(rect) if baseline == Some(glyph.position.y) => {
+                rect.min = rect.min.min(min);
+                rect.max = rect.max.max(max);
+            }
+            _ => {
+                baseline = Some(glyph.position.y);
+                rects.push(Rect { min, max });
+            }
+        }
+
+        carets.push(CaretStop {
+            index: char_indexer.char_index(sg.section_index, sg.byte_index),
+            line: rects.len() - 1,
+            left: min.x,
+            right: max.x,
+        });
+    }
+    carets.sort_by_key(|caret| caret.index);
+    (rects, carets)
+}
+
+/// Removes every glyph that doesn't lie entirely within `bounds`, measured from the top left of
+/// the laid out text, returning whether any glyphs were removed.
+fn clip_to_bounds(
+    section_glyphs: &mut Vec<SectionGlyph>,
+    section_fonts: &[SectionFont],
+    bounds: Size,
+) -> bool {
+    let (min_x, min_y) =
+        section_glyphs
+            .iter()
+            .fold((std::f32::MAX, std::f32::MAX), |(min_x, min_y), sg| {
+                let section_font = section_fonts[sg.section_index];
+                (
+                    min_x.min(sg.glyph.position.x),
+                    min_y.min(section_font.top(&sg.glyph)),
+                )
+            });
+
+    let len = section_glyphs.len();
+    section_glyphs.retain(|sg| {
+        let section_font = section_fonts[sg.section_index];
+        let glyph = &sg.glyph;
+        glyph.position.x + section_font.h_advance(glyph) - min_x <= bounds.width
+            && section_font.bottom(glyph) - min_y <= bounds.height
+    });
+    section_glyphs.len() != len
+}
+
+/// Removes the glyphs of every line after the first `max_lines`, returning whether any glyphs
+/// were removed.
+///
+/// All glyphs of a line share the same baseline, but empty lines produce no glyphs at all, so the
+/// hard line breaks in the text between two consecutive glyphs are counted as well.
+#[allow(clippy::float_cmp)]
+fn truncate_lines(
+    section_glyphs: &mut Vec<SectionGlyph>,
+    sections: &[SectionText],
+    max_lines: usize,
+) -> bool {
+    let mut line = 0;
+    let mut previous: Option<&SectionGlyph> = None;
+    let end = section_glyphs.iter().position(|sg| {
+        let position = (sg.section_index, sg.byte_index);
+        match previous {
+            Some(previous) if previous.glyph.position.y != sg.glyph.position.y => {
+                let newlines = count_newlines(
+                    sections,
+                    (previous.section_index, previous.byte_index),
+                    position,
+                );
+                line += newlines.max(1);
+            }
+            Some(_) => {}
+            None => line = count_newlines(sections, (0, 0), position),
+        }
+        previous = Some(sg);
+        line >= max_lines
+    });
+
+    match end {
+        Some(end) => {
+            section_glyphs.truncate(end);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Counts the `'\n'`s between two `(section_index, byte_index)` positions
+fn count_newlines(sections: &[SectionText], from: (usize, usize), to: (usize, usize)) -> usize {
+    (from.0..=to.0)
+        .map(|section_index| {
+            let text = sections[section_index].text;
+            let start = if section_index == from.0 { from.1 } else { 0 };
+            let end = if section_index == to.0 {
+                to.1
+            } else {
+                text.len()
+            };
+            text[start..end].matches('\n').count()
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TextLayoutInfo, TextPipeline};
+    use crate::{
+        draw::glyph_color, test_utils::app_with_font, Font, FontAtlasSet, MissingGlyphPolicy,
+        TextAlignment, TextAutoFit, TextSection, TextSettings, TextStyle,
+    };
+    use bevy_asset::Assets;
+    use bevy_math::{Size, Vec2};
+    use bevy_render::{color::Color, texture::Texture};
+    use bevy_sprite::{Rect, TextureAtlas};
+
+    /// Lays out one section per `(value, font_size)` in FiraMono at a scale factor of 1, where
+    /// every glyph advances by half the font size
+    fn layout(
+        values: &[(&str, f32)],
+        bounds: Size,
+        auto_fit: Option<TextAutoFit>,
+        text_settings: &TextSettings,
+    ) -> (Vec<TextSection>, TextLayoutInfo) {
+        let values = values
+            .iter()
+            .map(|(value, font_size)| {
+                let style = TextStyle {
+                    font_size: *font_size,
+                    ..Default::default()
+                };
+                (*value, style)
+            })
+            .collect::<Vec<_>>();
+        layout_styled(&values, None, bounds, auto_fit, text_settings)
+    }
+
+    /// Like [`layout`], but with a whole style per section, whose font is replaced by FiraMono
+    fn layout_styled(
+        values: &[(&str, TextStyle)],
+        max_lines: Option<usize>,
+        bounds: Size,
+        auto_fit: Option<TextAutoFit>,
+        text_settings: &TextSettings,
+    ) -> (Vec<TextSection>, TextLayoutInfo) {
+        let (mut app, font) = app_with_font();
+        let sections = values
+            .iter()
+            .map(|(value, style)| TextSection {
+                value: value.to_string(),
+                style: TextStyle {
+                    font: font.clone(),
+                    ..style.clone()
                 },
+            })
+            .collect::<Vec<_>>();
+
+        let world = app.world.cell();
+        let mut pipeline = TextPipeline::default();
+        pipeline
+            .queue_text(
+                0,
+                &world.get_resource::<Assets<Font>>().unwrap(),
+                &sections,
+                1.0,
+                TextAlignment::default(),
+                max_lines,
+                auto_fit,
+                bounds,
+                &mut world.get_resource_mut::<Assets<FontAtlasSet>>().unwrap(),
+                &mut world.get_resource_mut::<Assets<TextureAtlas>>().unwrap(),
+                &mut world.get_resource_mut::<Assets<Texture>>().unwrap(),
+                text_settings,
+            )
+            .unwrap();
+        let info = pipeline.glyph_map.remove(&0).unwrap();
+        (sections, info)
+    }
+
+    #[test]
+    fn line_rects_match_glyph_extents() {
+        let (_, info) = layout(
+            &[("ab\ncd", 20.)],
+            Size::new(f32::MAX, f32::MAX),
+            None,
+            &TextSettings::default(),
+        );
+
+        // two 10 pixel wide glyphs per line, and lines as tall as the font size
+        let expected = [
+            Rect {
+                min: Vec2::new(0., 20.),
+                max: Vec2::new(20., 40.),
+            },
+            Rect {
+                min: Vec2::new(0., 0.),
+                max: Vec2::new(20., 20.),
+            },
+        ];
+        assert_eq!(info.line_rects.len(), expected.len());
+        for (rect, expected) in info.line_rects.iter().zip(expected.iter()) {
+            assert!((rect.min - expected.min).abs().max_element() < 1e-3);
+            assert!((rect.max - expected.max).abs().max_element() < 1e-3);
+        }
+        assert!((info.size.width - 20.).abs() < 1e-3);
+        assert!((info.size.height - 40.).abs() < 1e-3);
+
+        assert_eq!(info.glyphs.len(), 4);
+        for (i, glyph) in info.glyphs.iter().enumerate() {
+            // glyph quads are rounded out to whole pixels
+            let rect = &info.line_rects[i / 2];
+            let half_size = glyph.size / 2.;
+            let (min, max) = (glyph.position - half_size, glyph.position + half_size);
+            assert!(min.cmpge(rect.min - Vec2::ONE).all());
+            assert!(max.cmple(rect.max + Vec2::ONE).all());
+        }
+    }
+
+    #[test]
+    fn missing_glyph_policies() {
+        let unbounded = Size::new(f32::MAX, f32::MAX);
+        let layout_with = |missing_glyph| {
+            let text_settings = TextSettings {
+                missing_glyph,
+                ..Default::default()
+            };
+            // FiraMono has no emoji
+            layout(&[("a\u{1F600}b", 20.)], unbounded, None, &text_settings).1
+        };
+        let char_indices = |info: &TextLayoutInfo| {
+            info.glyphs
+                .iter()
+                .map(|glyph| glyph.char_index)
+                .collect::<Vec<_>>()
+        };
+        let (_, question_mark) = layout(&[("?", 20.)], unbounded, None, &Default::default());
+        let question_mark_size = question_mark.glyphs[0].size;
+
+        // the hidden glyph still takes up its 10 pixel advance
+        let hidden = layout_with(MissingGlyphPolicy::Hidden);
+        assert_eq!(char_indices(&hidden), vec![0, 2]);
+        assert!(hidden.glyphs[1].position.x - hidden.glyphs[0].position.x > 15.);
+
+        let notdef = layout_with(MissingGlyphPolicy::Notdef);
+        assert_eq!(char_indices(&notdef), vec![0, 1, 2]);
+        assert_ne!(notdef.glyphs[1].size, question_mark_size);
+
+        let replaced = layout_with(MissingGlyphPolicy::Replace('?'));
+        assert_eq!(char_indices(&replaced), vec![0, 1, 2]);
+        assert_eq!(replaced.glyphs[1].size, question_mark_size);
+    }
+
+    #[test]
+    fn word_joiner_takes_no_space() {
+        // FiraMono has no glyph for the word joiner, so it must not fall back to .notdef
+        let policies = [
+            MissingGlyphPolicy::Hidden,
+            MissingGlyphPolicy::Notdef,
+            MissingGlyphPolicy::Replace('?'),
+        ];
+        for missing_glyph in policies.iter().copied() {
+            let text_settings = TextSettings {
+                missing_glyph,
+                ..Default::default()
+            };
+            let (_, unbounded) = layout(
+                &[("km\u{2060}/h", 20.)],
+                Size::new(f32::MAX, f32::MAX),
+                None,
+                &text_settings,
+            );
+            assert!(
+                (unbounded.size.width - 40.).abs() < 1e-3,
+                "{:?}",
+                missing_glyph
             );
-            return Ok(());
+            let char_indices = unbounded
+                .glyphs
+                .iter()
+                .map(|glyph| glyph.char_index)
+                .collect::<Vec<_>>();
+            assert_eq!(char_indices, vec![0, 1, 3, 4], "{:?}", missing_glyph);
+            assert_eq!(unbounded.caret_position(3), unbounded.caret_position(2));
+
+            // the joined run overflows instead of breaking after the slash
+            let (_, narrow) = layout(
+                &[("km\u{2060}/h", 20.)],
+                Size::new(25., f32::MAX),
+                None,
+                &text_settings,
+            );
+            assert_eq!(narrow.line_rects.len(), 1, "{:?}", missing_glyph);
         }
+    }
 
-        let mut min_x: f32 = std::f32::MAX;
-        let mut min_y: f32 = std::f32::MAX;
-        let mut max_x: f32 = std::f32::MIN;
-        let mut max_y: f32 = std::f32::MIN;
+    #[test]
+    fn replaced_multibyte_glyph_keeps_its_color() {
+        let text_settings = TextSettings {
+            missing_glyph: MissingGlyphPolicy::Replace('?'),
+            ..Default::default()
+        };
+        // replacing the 4 byte emoji with the 1 byte '?' shifts the byte indices after it
+        let (sections, info) = layout(
+            &[("a\u{1F600}b", 20.), ("c", 20.)],
+            Size::new(f32::MAX, f32::MAX),
+            None,
+            &text_settings,
+        );
+        let colors = [Color::RED, Color::GREEN, Color::BLUE, Color::YELLOW];
+        let glyph_colors = info
+            .glyphs
+            .iter()
+            .map(|glyph| glyph_color(glyph, &sections, Some(&colors[..])))
+            .collect::<Vec<_>>();
+        assert_eq!(glyph_colors, colors.to_vec());
+    }
+
+    #[test]
+    fn auto_fit_keeps_section_proportions() {
+        // at scale 1 the text is 2 * 20 + 10 = 50 pixels wide, and the bounds only fit one line
+        let (_, info) = layout(
+            &[("AB", 40.), ("c", 20.)],
+            Size::new(30., 28.),
+            Some(TextAutoFit::new(1., 100.)),
+            &TextSettings::default(),
+        );
+        // scaling both sections by 0.6 makes it fit, flattening them to one size would give 20
+        let fitted_font_size = info.fitted_font_size.unwrap();
+        assert!(
+            fitted_font_size <= 24. && fitted_font_size > 23.,
+            "{}",
+            fitted_font_size
+        );
+        assert!(info.size.width <= 30.);
+        let heights = info
+            .glyphs
+            .iter()
+            .map(|glyph| glyph.size.y)
+            .collect::<Vec<_>>();
+        assert!(heights[2] < heights[0] * 0.75, "{:?}", heights);
+    }
 
-        for sg in section_glyphs.iter() {
-            let scaled_font = scaled_fonts[sg.section_index];
-            let glyph = &sg.glyph;
-            min_x = min_x.min(glyph.position.x);
-            min_y = min_y.min(glyph.position.y - scaled_font.ascent());
-            max_x = max_x.max(glyph.position.x + scaled_font.h_advance(glyph.id));
-            max_y = max_y.max(glyph.position.y - scaled_font.descent());
+    #[test]
+    fn auto_fit_counts_lines_below_the_bounds() {
+        // at 20 pixels each word needs its own line, and the third one starts at the bottom edge
+        let (_, info) = layout(
+            &[("aaaa bbbb cccc", 20.)],
+            Size::new(50., 40.),
+            Some(TextAutoFit::new(1., 20.)),
+            &TextSettings::default(),
+        );
+        // three 40 / 3 pixel lines fit
+        let fitted_font_size = info.fitted_font_size.unwrap();
+        assert!(
+            fitted_font_size <= 40. / 3. && fitted_font_size > 12.5,
+            "{}",
+            fitted_font_size
+        );
+        assert_eq!(info.line_rects.len(), 3);
+        assert_eq!(info.glyphs.len(), 12);
+        assert!(info.size.height <= 40.);
+        assert!(!info.truncated);
+    }
+
+    #[test]
+    fn baseline_shift_grows_its_line() {
+        let superscript = TextStyle {
+            font_size: 10.,
+            baseline_shift: 1.2,
+            ..Default::default()
+        };
+        let normal = TextStyle {
+            font_size: 20.,
+            ..Default::default()
+        };
+        let (_, info) = layout_styled(
+            &[
+                ("x", normal.clone()),
+                ("2", superscript.clone()),
+                ("\ny", normal),
+                ("2", superscript),
+            ],
+            None,
+            Size::new(f32::MAX, f32::MAX),
+            None,
+            &TextSettings::default(),
+        );
+
+        // each superscript is raised 12 pixels, so its top ends up about 4 pixels above the
+        // ascent of the 20 pixel text, and both lines grow by that much
+        assert_eq!(info.line_rects.len(), 2);
+        for rect in info.line_rects.iter() {
+            let height = rect.max.y - rect.min.y;
+            assert!(height > 24. && height < 24.5, "{}", height);
         }
+        assert!(info.line_rects[0].min.y >= info.line_rects[1].max.y - 1e-3);
+        assert!(
+            info.size.height > 48. && info.size.height < 49.,
+            "{}",
+            info.size.height
+        );
 
-        let size = Size::new(max_x - min_x, max_y - min_y);
+        // the superscript on the second line stays below the first line's descent
+        let second_superscript = &info.glyphs[3];
+        let half_size = second_superscript.size / 2.;
+        assert!(second_superscript.position.y + half_size.y <= info.line_rects[0].min.y + 1.);
+    }
 
-        let glyphs = self.brush.process_glyphs(
-            section_glyphs,
-            &sections,
-            font_atlas_set_storage,
-            fonts,
-            texture_atlases,
-            textures,
-        )?;
+    #[test]
+    fn max_lines_counts_wrapped_and_empty_lines() {
+        let truncate = |values: &[&str], width: f32, max_lines: usize| {
+            let values = values
+                .iter()
+                .map(|value| {
+                    let style = TextStyle {
+                        font_size: 20.,
+                        ..Default::default()
+                    };
+                    (*value, style)
+                })
+                .collect::<Vec<_>>();
+            let (_, info) = layout_styled(
+                &values,
+                Some(max_lines),
+                Size::new(width, f32::MAX),
+                None,
+                &TextSettings::default(),
+            );
+            let char_indices = info
+                .glyphs
+                .iter()
+                .map(|glyph| glyph.char_index)
+                .collect::<Vec<_>>();
+            (char_indices, info.line_rects.len(), info.truncated)
+        };
+        let unbounded = f32::MAX;
 
-        self.glyph_map.insert(id, TextLayoutInfo { glyphs, size });
+        assert_eq!(truncate(&["a\nb"], unbounded, 2), (vec![0, 2], 2, false));
+        assert_eq!(truncate(&["a\nb\nc"], unbounded, 2), (vec![0, 2], 2, true));
+        // the empty line between "a" and "b" has no glyphs but still counts
+        assert_eq!(truncate(&["a\n\nb"], unbounded, 2), (vec![0], 1, true));
+        assert_eq!(truncate(&["\na"], unbounded, 1), (vec![], 0, true));
+        // line breaks are counted across sections
+        assert_eq!(truncate(&["a\n", "\nb"], unbounded, 2), (vec![0], 1, true));
+        assert_eq!(
+            truncate(&["a\n", "b"], unbounded, 2),
+            (vec![0, 2], 2, false)
+        );
+        // soft wraps count as lines, and the space ending the first line isn't drawn
+        assert_eq!(
+            truncate(&["aaaa bbbb"], 50., 1),
+            (vec![0, 1, 2, 3], 1, true)
+        );
+    }
 
-        Ok(())
+    fn assert_caret(info: &TextLayoutInfo, index: usize, expected: Vec2) {
+        let position = info.caret_position(index).unwrap();
+        assert!(
+            (position - expected).abs().max_element() < 1e-3,
+            "caret {} at {}, expected {}",
+            index,
+            position,
+            expected
+        );
+    }
+
+    #[test]
+    fn caret_position_at_line_boundaries() {
+        let (_, info) = layout(
+            &[("ab\ncd", 20.)],
+            Size::new(f32::MAX, f32::MAX),
+            None,
+            &TextSettings::default(),
+        );
+        assert_caret(&info, 0, Vec2::new(0., 20.));
+        assert_caret(&info, 1, Vec2::new(10., 20.));
+        // the line break puts the caret at the end of the first line
+        assert_caret(&info, 2, Vec2::new(20., 20.));
+        assert_caret(&info, 3, Vec2::new(0., 0.));
+        // past the end of the string
+        assert_caret(&info, 5, Vec2::new(20., 0.));
+        assert_caret(&info, 100, Vec2::new(20., 0.));
+    }
+
+    #[test]
+    fn caret_position_across_sections_and_soft_wraps() {
+        // "abcd " fills the first line, so "ef" wraps onto the second one
+        let (_, info) = layout(
+            &[("ab", 20.), ("cd ef", 20.)],
+            Size::new(45., f32::MAX),
+            None,
+            &TextSettings::default(),
+        );
+        assert_eq!(info.line_rects.len(), 2);
+        assert_caret(&info, 1, Vec2::new(10., 20.));
+        // the first character of the second section
+        assert_caret(&info, 2, Vec2::new(20., 20.));
+        assert_caret(&info, 3, Vec2::new(30., 20.));
+        // the space that ends the first line
+        assert_caret(&info, 4, Vec2::new(40., 20.));
+        // the first character after the wrap starts the second line
+        assert_caret(&info, 5, Vec2::new(0., 0.));
+        assert_caret(&info, 7, Vec2::new(20., 0.));
+    }
+
+    #[test]
+    fn index_at_position_picks_nearest_caret() {
+        let (_, info) = layout(
+            &[("ab\ncd", 20.)],
+            Size::new(f32::MAX, f32::MAX),
+            None,
+            &TextSettings::default(),
+        );
+        assert_eq!(info.index_at_position(Vec2::new(-5., 30.)), 0);
+        assert_eq!(info.index_at_position(Vec2::new(6., 30.)), 1);
+        assert_eq!(info.index_at_position(Vec2::new(50., 30.)), 2);
+        assert_eq!(info.index_at_position(Vec2::new(4., 10.)), 3);
+        assert_eq!(info.index_at_position(Vec2::new(50., 10.)), 5);
+        // above the first line and below the last one
+        assert_eq!(info.index_at_position(Vec2::new(0., 100.)), 0);
+        assert_eq!(info.index_at_position(Vec2::new(50., -100.)), 5);
+    }
+
+    #[test]
+    fn index_at_position_across_sections_and_soft_wraps() {
+        let (_, info) = layout(
+            &[("ab", 20.), ("cd ef", 20.)],
+            Size::new(45., f32::MAX),
+            None,
+            &TextSettings::default(),
+        );
+        // either side of the section boundary
+        assert_eq!(info.index_at_position(Vec2::new(14., 30.)), 1);
+        assert_eq!(info.index_at_position(Vec2::new(16., 30.)), 2);
+        assert_eq!(info.index_at_position(Vec2::new(24., 30.)), 2);
+        // past the space that ends the first line, and at the start of the wrapped line
+        assert_eq!(info.index_at_position(Vec2::new(48., 30.)), 5);
+        assert_eq!(info.index_at_position(Vec2::new(4., 10.)), 5);
+        assert_eq!(info.index_at_position(Vec2::new(16., 10.)), 7);
+    }
+
+    #[test]
+    fn no_glyphs_means_no_caret() {
+        let info = TextLayoutInfo {
+            glyphs: Vec::new(),
+            size: Size::new(0., 0.),
+            truncated: false,
+            line_rects: Vec::new(),
+            fitted_font_size: None,
+            caret_stops: Vec::new(),
+        };
+        assert_eq!(info.caret_position(0), None);
+        assert_eq!(info.index_at_position(Vec2::ZERO), 0);
     }
 }
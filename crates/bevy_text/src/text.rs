@@ -3,12 +3,23 @@ use bevy_math::Size;
 use bevy_render::color::Color;
 use glyph_brush_layout::{HorizontalAlign, VerticalAlign};
 
-use crate::Font;
+use crate::{Font, RasterizationMode};
 
 #[derive(Debug, Default, Clone)]
 pub struct Text {
     pub sections: Vec<TextSection>,
     pub alignment: TextAlignment,
+    /// Colors overriding [`TextStyle::color`] for individual characters, indexed by character
+    /// across all sections (whitespace included). Characters past the end of the list use the
+    /// color of their section.
+    pub per_glyph_colors: Option<Vec<Color>>,
+    /// Maximum number of lines to lay out; any further lines are dropped. When the layout bounds
+    /// are too short to fit this many lines, the bounds win.
+    pub max_lines: Option<usize>,
+    /// Shrinks or grows the text to the largest font size that fits within its layout bounds.
+    /// The fitted size of the largest section is reported in
+    /// [`TextLayoutInfo::fitted_font_size`](crate::TextLayoutInfo).
+    pub auto_fit: Option<TextAutoFit>,
 }
 
 impl Text {
@@ -29,6 +40,7 @@ impl Text {
     ///         font: font_handle.clone(),
     ///         font_size: 60.0,
     ///         color: Color::WHITE,
+    ///         ..Default::default()
     ///     },
     ///     TextAlignment {
     ///         vertical: VerticalAlign::Center,
@@ -43,6 +55,7 @@ impl Text {
     ///         font: font_handle,
     ///         font_size: 60.0,
     ///         color: Color::WHITE,
+    ///         ..Default::default()
     ///     },
     ///     // you can still use Default
     ///     Default::default(),
@@ -59,6 +72,32 @@ impl Text {
                 style,
             }],
             alignment,
+            ..Default::default()
+        }
+    }
+}
+
+/// Picks the font size of a [`Text`] so that it fits within its layout bounds.
+///
+/// `min_size` and `max_size` limit the [`TextStyle::font_size`] of the largest section. The other
+/// sections are scaled by the same factor, so mixed sizes such as subscripts keep their
+/// proportions. Text without bounds, such as [`Text2dBundle`](crate::Text2dBundle) text, always
+/// gets `max_size`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextAutoFit {
+    pub min_size: f32,
+    pub max_size: f32,
+    /// When even `min_size` doesn't fit, drop the glyphs that overflow the bounds instead of
+    /// drawing them outside
+    pub clip_overflow: bool,
+}
+
+impl TextAutoFit {
+    pub fn new(min_size: f32, max_size: f32) -> Self {
+        Self {
+            min_size,
+            max_size,
+            clip_overflow: false,
         }
     }
 }
@@ -69,6 +108,21 @@ pub struct TextSection {
     pub style: TextStyle,
 }
 
+impl TextSection {
+    /// Constructs a [`TextSection`] that is never wrapped internally, by replacing its spaces with
+    /// no-break spaces. If it doesn't fit on the current line it moves to the next one whole, and
+    /// it overflows the bounds if it is wider than them.
+    ///
+    /// To keep a single word such as "km/h" together, join its parts with a word joiner
+    /// (U+2060) instead.
+    pub fn no_break<S: Into<String>>(value: S, style: TextStyle) -> Self {
+        Self {
+            value: value.into().replace(' ', "\u{A0}"),
+            style,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct TextAlignment {
     pub vertical: VerticalAlign,
@@ -89,6 +143,14 @@ pub struct TextStyle {
     pub font: Handle<Font>,
     pub font_size: f32,
     pub color: Color,
+    /// Moves the section's glyphs up (positive) or down (negative) from the line's baseline, as a
+    /// fraction of `font_size`. Pair it with a smaller `font_size` for superscripts and
+    /// subscripts. A line grows by however far its shifted glyphs reach past its unshifted
+    /// glyphs, so they never overlap the neighbouring lines.
+    pub baseline_shift: f32,
+    /// How the section's glyphs are rasterized. Use [`RasterizationMode::Pixelated`] for pixel
+    /// fonts.
+    pub rasterization: RasterizationMode,
 }
 
 impl Default for TextStyle {
@@ -97,6 +159,8 @@ impl Default for TextStyle {
             font: Default::default(),
             font_size: 12.0,
             color: Color::WHITE,
+            baseline_shift: 0.0,
+            rasterization: RasterizationMode::Antialiased,
         }
     }
 }
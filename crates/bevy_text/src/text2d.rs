@@ -18,7 +18,10 @@ use bevy_transform::prelude::{GlobalTransform, Transform};
 use bevy_window::Windows;
 use glyph_brush_layout::{HorizontalAlign, VerticalAlign};
 
-use crate::{DefaultTextPipeline, DrawableText, Font, FontAtlasSet, Text, Text2dSize, TextError};
+use crate::{
+    DefaultTextPipeline, DrawableText, Font, FontAtlasSet, Text, Text2dSize, TextError,
+    TextSettings,
+};
 
 /// The bundle of components needed to draw text in a 2D scene via a 2D `OrthographicCameraBundle`.
 #[derive(Bundle, Clone, Debug)]
@@ -112,6 +115,7 @@ pub fn draw_text2d_system(
                 font_quad_vertex_layout: &font_quad_vertex_layout,
                 scale_factor,
                 sections: &text.sections,
+                per_glyph_colors: text.per_glyph_colors.as_deref(),
             };
 
             drawable_text.draw(&mut draw, &mut context).unwrap();
@@ -134,14 +138,23 @@ pub fn text2d_system(
     mut texture_atlases: ResMut<Assets<TextureAtlas>>,
     mut font_atlas_set_storage: ResMut<Assets<FontAtlasSet>>,
     mut text_pipeline: ResMut<DefaultTextPipeline>,
+    text_settings: Res<TextSettings>,
     mut text_queries: QuerySet<(
         Query<Entity, (With<MainPass>, Changed<Text>)>,
+        Query<Entity, (With<MainPass>, With<Text>)>,
         Query<(&Text, &mut Text2dSize), With<MainPass>>,
     )>,
 ) {
-    // Adds all entities where the text or the style has changed to the local queue
-    for entity in text_queries.q0_mut().iter_mut() {
-        queued_text.entities.push(entity);
+    if text_settings.is_changed() {
+        // The text settings affect the layout of all text, so queue all of it
+        for entity in text_queries.q1().iter() {
+            queued_text.entities.push(entity);
+        }
+    } else {
+        // Adds all entities where the text or the style has changed to the local queue
+        for entity in text_queries.q0_mut().iter_mut() {
+            queued_text.entities.push(entity);
+        }
     }
 
     if queued_text.entities.is_empty() {
@@ -156,7 +169,7 @@ pub fn text2d_system(
 
     // Computes all text in the local queue
     let mut new_queue = Vec::new();
-    let query = text_queries.q1_mut();
+    let query = text_queries.q2_mut();
     for entity in queued_text.entities.drain(..) {
         if let Ok((text, mut calculated_size)) = query.get_mut(entity) {
             match text_pipeline.queue_text(
@@ -165,10 +178,13 @@ pub fn text2d_system(
                 &text.sections,
                 scale_factor,
                 text.alignment,
+                text.max_lines,
+                text.auto_fit,
                 Size::new(f32::MAX, f32::MAX),
                 &mut *font_atlas_set_storage,
                 &mut *texture_atlases,
                 &mut *textures,
+                &text_settings,
             ) {
                 Err(TextError::NoSuchFont) => {
                     // There was an error processing the text layout, let's add this entity to the
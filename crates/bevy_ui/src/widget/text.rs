@@ -14,7 +14,9 @@ use bevy_render::{
     texture::Texture,
 };
 use bevy_sprite::{TextureAtlas, QUAD_HANDLE};
-use bevy_text::{DefaultTextPipeline, DrawableText, Font, FontAtlasSet, Text, TextError};
+use bevy_text::{
+    DefaultTextPipeline, DrawableText, Font, FontAtlasSet, Text, TextError, TextSettings,
+};
 use bevy_transform::prelude::GlobalTransform;
 use bevy_window::Windows;
 
@@ -52,6 +54,7 @@ pub fn text_system(
     mut texture_atlases: ResMut<Assets<TextureAtlas>>,
     mut font_atlas_set_storage: ResMut<Assets<FontAtlasSet>>,
     mut text_pipeline: ResMut<DefaultTextPipeline>,
+    text_settings: Res<TextSettings>,
     mut text_queries: QuerySet<(
         Query<Entity, Or<(Changed<Text>, Changed<Style>)>>,
         Query<Entity, (With<Text>, With<Style>)>,
@@ -67,13 +70,13 @@ pub fn text_system(
     let inv_scale_factor = 1. / scale_factor;
 
     #[allow(clippy::float_cmp)]
-    if *last_scale_factor == scale_factor {
+    if *last_scale_factor == scale_factor && !text_settings.is_changed() {
         // Adds all entities where the text or the style has changed to the local queue
         for entity in text_queries.q0().iter() {
             queued_text.entities.push(entity);
         }
     } else {
-        // If the scale factor has changed, queue all text
+        // If the scale factor or the text settings have changed, queue all text
         for entity in text_queries.q1().iter() {
             queued_text.entities.push(entity);
         }
@@ -110,10 +113,13 @@ pub fn text_system(
                 &text.sections,
                 scale_factor,
                 text.alignment,
+                text.max_lines,
+                text.auto_fit,
                 node_size,
                 &mut *font_atlas_set_storage,
                 &mut *texture_atlases,
                 &mut *textures,
+                &text_settings,
             ) {
                 Err(TextError::NoSuchFont) => {
                     // There was an error processing the text layout, let's add this entity to the
@@ -177,6 +183,7 @@ pub fn draw_text_system(
                 text_glyphs: &text_glyphs.glyphs,
                 font_quad_vertex_layout: &vertex_buffer_layout,
                 sections: &text.sections,
+                per_glyph_colors: text.per_glyph_colors.as_deref(),
             };
 
             drawable_text.draw(&mut draw, &mut context).unwrap();
@@ -126,6 +126,7 @@ fn setup(
                             font: asset_server.load("fonts/FiraSans-Bold.ttf"),
                             font_size: 60.0,
                             color: Color::WHITE,
+                            ..Default::default()
                         },
                     },
                     TextSection {
@@ -134,6 +135,7 @@ fn setup(
                             font: asset_server.load("fonts/FiraSans-Bold.ttf"),
                             font_size: 60.0,
                             color: Color::WHITE,
+                            ..Default::default()
                         },
                     },
                 ],
@@ -0,0 +1,44 @@
+use bevy::{prelude::*, text::RasterizationMode};
+
+/// Draws small text through a zoomed in camera, once antialiased and once pixelated, to show how
+/// `RasterizationMode::Pixelated` keeps hard pixel edges when text is magnified
+fn main() {
+    App::build()
+        .add_plugins(DefaultPlugins)
+        .add_startup_system(setup.system())
+        .run();
+}
+
+fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = asset_server.load("fonts/FiraMono-Medium.ttf");
+
+    // magnify everything 6 times, like a low resolution pixel art game
+    let mut camera = OrthographicCameraBundle::new_2d();
+    camera.orthographic_projection.scale = 1.0 / 6.0;
+    commands.spawn_bundle(camera);
+
+    let modes = [
+        (RasterizationMode::Antialiased, 12.0),
+        (RasterizationMode::Pixelated, -12.0),
+    ];
+    for (rasterization, y) in modes.iter().copied() {
+        commands.spawn_bundle(Text2dBundle {
+            text: Text::with_section(
+                format!("{:?}", rasterization),
+                TextStyle {
+                    font: font.clone(),
+                    font_size: 12.0,
+                    color: Color::WHITE,
+                    rasterization,
+                    ..Default::default()
+                },
+                TextAlignment {
+                    vertical: VerticalAlign::Center,
+                    horizontal: HorizontalAlign::Center,
+                },
+            ),
+            transform: Transform::from_xyz(0.0, y, 0.0),
+            ..Default::default()
+        });
+    }
+}
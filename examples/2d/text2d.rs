@@ -18,6 +18,7 @@ fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
                 font: asset_server.load("fonts/FiraSans-Bold.ttf"),
                 font_size: 60.0,
                 color: Color::WHITE,
+                ..Default::default()
             },
             TextAlignment {
                 vertical: VerticalAlign::Center,
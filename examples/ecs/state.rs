@@ -59,6 +59,7 @@ fn setup_menu(
                         font: asset_server.load("fonts/FiraSans-Bold.ttf"),
                         font_size: 40.0,
                         color: Color::rgb(0.9, 0.9, 0.9),
+                        ..Default::default()
                     },
                     Default::default(),
                 ),
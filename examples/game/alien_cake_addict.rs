@@ -162,6 +162,7 @@ fn setup(mut commands: Commands, asset_server: Res<AssetServer>, mut game: ResMu
                 font: asset_server.load("fonts/FiraSans-Bold.ttf"),
                 font_size: 40.0,
                 color: Color::rgb(0.5, 0.5, 1.0),
+                ..Default::default()
             },
             Default::default(),
         ),
@@ -388,6 +389,7 @@ fn display_score(
                         font: asset_server.load("fonts/FiraSans-Bold.ttf"),
                         font_size: 80.0,
                         color: Color::rgb(0.5, 0.5, 1.0),
+                        ..Default::default()
                     },
                     Default::default(),
                 ),
@@ -83,6 +83,7 @@ fn setup(
                         font: asset_server.load("fonts/FiraSans-Bold.ttf"),
                         font_size: 40.0,
                         color: Color::rgb(0.5, 0.5, 1.0),
+                        ..Default::default()
                     },
                 },
                 TextSection {
@@ -91,6 +92,7 @@ fn setup(
                         font: asset_server.load("fonts/FiraMono-Medium.ttf"),
                         font_size: 40.0,
                         color: Color::rgb(1.0, 0.5, 0.5),
+                        ..Default::default()
                     },
                 },
             ],
@@ -115,6 +115,7 @@ fn infotext_system(mut commands: Commands, asset_server: Res<AssetServer>) {
                 font: asset_server.load("fonts/FiraSans-Bold.ttf"),
                 font_size: 50.0,
                 color: Color::WHITE,
+                ..Default::default()
             },
             Default::default(),
         ),
@@ -64,6 +64,7 @@ fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
                         font: asset_server.load("fonts/FiraSans-Bold.ttf"),
                         font_size: 40.0,
                         color: Color::rgb(0.0, 1.0, 0.0),
+                        ..Default::default()
                     },
                 },
                 TextSection {
@@ -72,6 +73,7 @@ fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
                         font: asset_server.load("fonts/FiraSans-Bold.ttf"),
                         font_size: 40.0,
                         color: Color::rgb(0.0, 1.0, 1.0),
+                        ..Default::default()
                     },
                 },
                 TextSection {
@@ -80,6 +82,7 @@ fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
                         font: asset_server.load("fonts/FiraSans-Bold.ttf"),
                         font_size: 40.0,
                         color: Color::rgb(0.0, 1.0, 0.0),
+                        ..Default::default()
                     },
                 },
                 TextSection {
@@ -88,6 +91,7 @@ fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
                         font: asset_server.load("fonts/FiraSans-Bold.ttf"),
                         font_size: 40.0,
                         color: Color::rgb(0.0, 1.0, 1.0),
+                        ..Default::default()
                     },
                 },
             ],
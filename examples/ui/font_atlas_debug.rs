@@ -88,6 +88,7 @@ fn setup(mut commands: Commands, asset_server: Res<AssetServer>, mut state: ResM
                 font: font_handle,
                 font_size: 60.0,
                 color: Color::YELLOW,
+                ..Default::default()
             },
             Default::default(),
         ),
@@ -46,6 +46,7 @@ fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
                     font: asset_server.load("fonts/FiraSans-Bold.ttf"),
                     font_size: 100.0,
                     color: Color::WHITE,
+                    ..Default::default()
                 },
                 // Note: You can use `Default::default()` in place of the `TextAlignment`
                 TextAlignment {
@@ -73,6 +74,7 @@ fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
                             font: asset_server.load("fonts/FiraSans-Bold.ttf"),
                             font_size: 60.0,
                             color: Color::WHITE,
+                            ..Default::default()
                         },
                     },
                     TextSection {
@@ -81,6 +83,7 @@ fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
                             font: asset_server.load("fonts/FiraMono-Medium.ttf"),
                             font_size: 60.0,
                             color: Color::GOLD,
+                            ..Default::default()
                         },
                     },
                 ],
@@ -1,6 +1,7 @@
 use bevy::{
     diagnostic::{Diagnostics, FrameTimeDiagnosticsPlugin},
     prelude::*,
+    text::{GlyphPositioning, TextSettings},
 };
 
 /// This example is for debugging text layout
@@ -14,12 +15,26 @@ fn main() {
         .add_plugin(FrameTimeDiagnosticsPlugin)
         .add_startup_system(infotext_system.system())
         .add_system(change_text_system.system())
+        .add_system(toggle_glyph_positioning_system.system())
         .run();
 }
 
 struct TextChanges;
 
-fn infotext_system(mut commands: Commands, asset_server: Res<AssetServer>) {
+struct GlyphPositioningSample;
+
+fn glyph_positioning_sample(glyph_positioning: GlyphPositioning) -> String {
+    format!(
+        "Small text with {:?} glyph positioning (press space to toggle)",
+        glyph_positioning
+    )
+}
+
+fn infotext_system(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    text_settings: Res<TextSettings>,
+) {
     let font = asset_server.load("fonts/FiraSans-Bold.ttf");
     commands.spawn_bundle(UiCameraBundle::default());
     commands.spawn_bundle(TextBundle {
@@ -39,6 +54,7 @@ fn infotext_system(mut commands: Commands, asset_server: Res<AssetServer>) {
                 font: font.clone(),
                 font_size: 50.0,
                 color: Color::WHITE,
+                ..Default::default()
             },
             Default::default(),
         ),
@@ -65,6 +81,7 @@ fn infotext_system(mut commands: Commands, asset_server: Res<AssetServer>) {
                     font: font.clone(),
                     font_size: 50.0,
                     color: Color::rgb(0.8, 0.2, 0.7),
+                    ..Default::default()
                 },
             TextAlignment {
                 horizontal: HorizontalAlign::Center,
@@ -73,6 +90,63 @@ fn infotext_system(mut commands: Commands, asset_server: Res<AssetServer>) {
         ),
         ..Default::default()
     });
+    let script = |value: &str, font_size, baseline_shift| TextSection {
+        value: value.to_string(),
+        style: TextStyle {
+            font: font.clone(),
+            font_size,
+            color: Color::WHITE,
+            baseline_shift,
+            ..Default::default()
+        },
+    };
+    commands.spawn_bundle(TextBundle {
+        style: Style {
+            align_self: AlignSelf::FlexEnd,
+            position_type: PositionType::Absolute,
+            position: Rect {
+                top: Val::Px(260.0),
+                left: Val::Px(15.0),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        text: Text {
+            sections: vec![
+                script("H", 50.0, 0.0),
+                script("2", 30.0, -0.3),
+                script("O and x", 50.0, 0.0),
+                script("2", 30.0, 0.7),
+            ],
+            ..Default::default()
+        },
+        ..Default::default()
+    });
+    commands
+        .spawn_bundle(TextBundle {
+            style: Style {
+                align_self: AlignSelf::FlexEnd,
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    top: Val::Px(330.0),
+                    left: Val::Px(15.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            text: Text::with_section(
+                glyph_positioning_sample(text_settings.glyph_positioning),
+                TextStyle {
+                    font: font.clone(),
+                    font_size: 11.0,
+                    color: Color::WHITE,
+                    ..Default::default()
+                },
+                Default::default(),
+            ),
+            ..Default::default()
+        })
+        .insert(GlyphPositioningSample);
     commands
         .spawn_bundle(TextBundle {
             style: Style {
@@ -93,6 +167,7 @@ fn infotext_system(mut commands: Commands, asset_server: Res<AssetServer>) {
                             font: font.clone(),
                             font_size: 30.0,
                             color: Color::WHITE,
+                            ..Default::default()
                         },
                     },
                     TextSection {
@@ -101,6 +176,7 @@ fn infotext_system(mut commands: Commands, asset_server: Res<AssetServer>) {
                             font: font.clone(),
                             font_size: 30.0,
                             color: Color::RED,
+                            ..Default::default()
                         },
                     },
                     TextSection {
@@ -109,6 +185,7 @@ fn infotext_system(mut commands: Commands, asset_server: Res<AssetServer>) {
                             font: font.clone(),
                             font_size: 30.0,
                             color: Color::ORANGE_RED,
+                            ..Default::default()
                         },
                     },
                     TextSection {
@@ -117,6 +194,7 @@ fn infotext_system(mut commands: Commands, asset_server: Res<AssetServer>) {
                             font: font.clone(),
                             font_size: 30.0,
                             color: Color::YELLOW,
+                            ..Default::default()
                         },
                     },
                     TextSection {
@@ -125,6 +203,7 @@ fn infotext_system(mut commands: Commands, asset_server: Res<AssetServer>) {
                             font: font.clone(),
                             font_size: 30.0,
                             color: Color::GREEN,
+                            ..Default::default()
                         },
                     },
                     TextSection {
@@ -133,10 +212,11 @@ fn infotext_system(mut commands: Commands, asset_server: Res<AssetServer>) {
                             font: font.clone(),
                             font_size: 30.0,
                             color: Color::BLUE,
+                            ..Default::default()
                         },
                     },
                 ],
-                alignment: Default::default(),
+                ..Default::default()
             },
             ..Default::default()
         })
@@ -162,6 +242,7 @@ fn infotext_system(mut commands: Commands, asset_server: Res<AssetServer>) {
                 font,
                 font_size: 50.0,
                 color: Color::WHITE,
+                ..Default::default()
             },
             Default::default(),
         ),
@@ -201,3 +282,21 @@ fn change_text_system(
         text.sections[4].value = format!("{:.3}", frame_time * 1000.0);
     }
 }
+
+fn toggle_glyph_positioning_system(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut text_settings: ResMut<TextSettings>,
+    mut query: Query<&mut Text, With<GlyphPositioningSample>>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::Space) {
+        return;
+    }
+    text_settings.glyph_positioning = match text_settings.glyph_positioning {
+        GlyphPositioning::PixelSnapped => GlyphPositioning::Subpixel,
+        GlyphPositioning::Subpixel => GlyphPositioning::PixelSnapped,
+    };
+    // all text is laid out again with the new setting, this only updates the label
+    for mut text in query.iter_mut() {
+        text.sections[0].value = glyph_positioning_sample(text_settings.glyph_positioning);
+    }
+}
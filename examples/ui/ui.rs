@@ -63,6 +63,7 @@ fn setup(
                                         font: asset_server.load("fonts/FiraSans-Bold.ttf"),
                                         font_size: 30.0,
                                         color: Color::WHITE,
+                                        ..Default::default()
                                     },
                                     Default::default(),
                                 ),